@@ -0,0 +1,121 @@
+use crate::inputtypes::InputError;
+
+/// Represents the command line arguments passed to the cli.
+#[derive(Debug, Default)]
+pub struct Args {
+    /// Name of the configuration profile to use.
+    pub profile: Option<String>,
+    /// Switch label, IP address or DeviceID for a reverse lookup, or to list
+    /// all wallsockets patched to the switch if no port is given.
+    pub switch: Option<String>,
+    /// Switch port label for a reverse lookup.
+    pub port: Option<String>,
+    /// File to read wallsocket descriptions from, one per line, or `-` for
+    /// standard input.
+    pub batch: Option<String>,
+    /// Text to search port identifiers, switch hostnames and IPs for.
+    pub search: Option<String>,
+    /// Whether to print hardware and ownership details of the switch.
+    pub details: bool,
+}
+
+impl Args {
+    /// Parses the arguments the cli was started with.
+    pub fn parse() -> Result<Args, InputError> {
+        Args::parse_from(std::env::args().skip(1))
+    }
+
+    /// Parses the given arguments, without the program name.
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Args, InputError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--details" {
+                parsed.details = true;
+                continue;
+            }
+            let value = match arg.as_str() {
+                "--profile" | "--switch" | "--port" | "--batch" | "--search" => args
+                    .next()
+                    .ok_or_else(|| InputError::new(&format!("{arg} needs a value")))?,
+                _ => return Err(InputError::new(&format!("Unknown argument: {arg}"))),
+            };
+            match arg.as_str() {
+                "--profile" => parsed.profile = Some(value),
+                "--switch" => parsed.switch = Some(value),
+                "--batch" => parsed.batch = Some(value),
+                "--search" => parsed.search = Some(value),
+                _ => parsed.port = Some(value),
+            }
+        }
+
+        if parsed.port.is_some() && parsed.switch.is_none() {
+            return Err(InputError::new("--port needs --switch"));
+        }
+        if parsed.batch.is_some() && parsed.switch.is_some() {
+            return Err(InputError::new("--batch can't be combined with --switch"));
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse_from(args.iter().map(|arg| arg.to_string())).map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn parses_every_flag() {
+        let args = parse(&[
+            "--profile",
+            "test",
+            "--switch",
+            "sw-1",
+            "--port",
+            "Gi1/0/1",
+            "--details",
+        ])
+        .unwrap();
+        assert_eq!(args.profile.as_deref(), Some("test"));
+        assert_eq!(args.switch.as_deref(), Some("sw-1"));
+        assert_eq!(args.port.as_deref(), Some("Gi1/0/1"));
+        assert!(args.details);
+
+        let args = parse(&["--batch", "-", "--search", "01.1"]).unwrap();
+        assert_eq!(args.batch.as_deref(), Some("-"));
+        assert_eq!(args.search.as_deref(), Some("01.1"));
+    }
+
+    #[test]
+    fn rejects_batch_with_switch() {
+        assert_eq!(
+            parse(&["--batch", "sockets.txt", "--switch", "sw-1"]).unwrap_err(),
+            "--batch can't be combined with --switch"
+        );
+    }
+
+    #[test]
+    fn rejects_port_without_switch() {
+        assert_eq!(
+            parse(&["--port", "Gi1/0/1"]).unwrap_err(),
+            "--port needs --switch"
+        );
+    }
+
+    #[test]
+    fn rejects_missing_values() {
+        assert_eq!(parse(&["--switch"]).unwrap_err(), "--switch needs a value");
+    }
+
+    #[test]
+    fn rejects_unknown_flags() {
+        assert_eq!(
+            parse(&["--verbose"]).unwrap_err(),
+            "Unknown argument: --verbose"
+        );
+    }
+}
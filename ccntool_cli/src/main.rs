@@ -1,17 +1,24 @@
+// Import the `Args` type from the `args` module.
+mod args;
+use args::Args;
+
 // Import the `InputError` type from the `inputtypes` module.
 mod inputtypes;
 use inputtypes::InputError;
 
-// Import the `connectdb` and `myquery` functions from the `ccntool_core` crate.
-use ccntool_core::{connectdb, myquery};
+// Import the blocking `DcimClient` and the configuration from the `ccntool_core` crate.
+use ccntool_core::blocking::DcimClient;
+use ccntool_core::config::{Config, ConfigLayer};
+use ccntool_core::{Error, PortLookup, RoomId, SearchEntry, SearchKind};
 
-// Import the `io` module from the Rust standard library.
-use std::io::{self, Write};
+// Import the `io` and `process` modules from the Rust standard library.
+use std::io::{self, BufRead, BufReader, Write};
+use std::{fs::File, process};
 
 // Define a function for getting user input.
 fn userinput() -> std::result::Result<String, InputError> {
     let mut notes = String::new();
-    print!("Enter wallsocket description: ");
+    print!("Enter wallsocket or room description: ");
     io::stdout().flush().unwrap();
 
     // Read a line of input from the user.
@@ -29,44 +36,363 @@ fn userinput() -> std::result::Result<String, InputError> {
     }
 }
 
+// Define a function for printing the result of a lookup, optionally with
+// the details of the switch.
+fn printlookup(client: &DcimClient, lookup: &PortLookup, details: bool) {
+    println!("\n\t\tHere is what I know:\n");
+    println!("Wallsocket: {}", lookup.wallsocket());
+    if let Some(kind) = lookup.kind {
+        match kind.hint() {
+            Some(hint) => println!("Kind: {hint}"),
+            None => println!("Kind: {kind}"),
+        }
+    }
+    if let Some(socket) = lookup.socket_id() {
+        println!(
+            "Location: building {}, floor {}, room {}",
+            socket.building(),
+            socket.floor(),
+            socket.room()
+        );
+    }
+    println!("Switchname: {}", lookup.switch_label);
+    if let Some(chassis) = lookup.switch_chassis() {
+        println!("Chassis: {chassis}");
+    }
+    if let Some(location) = lookup.switch_location() {
+        println!("Switch location: {location}");
+    }
+    println!("IP: {}", lookup.switch_ip_text());
+    println!("Switchport: {}", lookup.switch_port);
+    println!("Description: {}", lookup.port_notes);
+    println!("Path:");
+    for hop in &lookup.path {
+        match &hop.location {
+            Some(location) => println!("  {hop} ({location})"),
+            None => println!("  {hop}"),
+        }
+    }
+    if let Some(switch_details) = lookup.switch_details.as_ref().filter(|_| details) {
+        println!("Switch details:");
+        for (label, value) in switch_details.fields() {
+            println!("  {label}: {value}");
+        }
+    }
+    if details {
+        match client.uplinks(&lookup.switch_device_id.to_string()) {
+            Ok(uplinks) if uplinks.is_empty() => println!("Uplinks: none found"),
+            Ok(uplinks) => {
+                println!("Uplinks:");
+                for uplink in uplinks {
+                    match uplink.switch_ip {
+                        Some(ip) => println!("  {uplink} ({ip})"),
+                        None => println!("  {uplink}"),
+                    }
+                }
+            }
+            Err(error) => eprintln!("Could not follow the uplinks: {error}"),
+        }
+    }
+    let url: String = client.config().device_url(lookup.switch_device_id);
+    println!("{url}");
+}
+
+// Define a function for printing rows as a table with aligned columns.
+fn printtable(headers: &[&str], rows: &[Vec<String>]) {
+    // Size the columns to fit their widest entry.
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            rows.iter()
+                .map(|row| row[column].len())
+                .max()
+                .unwrap_or(0)
+                .max(header.len())
+        })
+        .collect();
+
+    let line = |cells: Vec<String>| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    };
+
+    println!();
+    line(headers.iter().map(|header| header.to_string()).collect());
+    line(widths.iter().map(|width| "-".repeat(*width)).collect());
+    for row in rows {
+        line(row.clone());
+    }
+}
+
+// Define a function for printing the wallsockets patched to a switch as a table.
+fn printsockets(lookups: &[PortLookup]) {
+    if lookups.is_empty() {
+        println!("\nNo wallsockets are patched to this switch.");
+        return;
+    }
+
+    let rows: Vec<Vec<String>> = lookups
+        .iter()
+        .map(|lookup| {
+            vec![
+                lookup.switch_port.clone(),
+                lookup.wallsocket().to_owned(),
+                lookup.port_notes.clone(),
+            ]
+        })
+        .collect();
+    printtable(&["Port", "Wallsocket", "Description"], &rows);
+}
+
+// Define a function for printing the wallsockets in a room as a table.
+fn printroom(lookups: &[PortLookup]) {
+    if lookups.is_empty() {
+        println!("\nNo wallsocket in this room is patched to a switch.");
+        return;
+    }
+
+    let rows: Vec<Vec<String>> = lookups
+        .iter()
+        .map(|lookup| {
+            vec![
+                lookup.wallsocket().to_owned(),
+                lookup.switch_label.clone(),
+                lookup.switch_port.clone(),
+                lookup.switch_ip_text(),
+            ]
+        })
+        .collect();
+    printtable(&["Wallsocket", "Switch", "Port", "IP"], &rows);
+}
+
+// Define a function for printing search results as a table, best match first.
+fn printsearch(entries: &[SearchEntry]) {
+    if entries.is_empty() {
+        println!("\nNothing matches.");
+        return;
+    }
+
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        // Only the best matches are of interest.
+        .take(20)
+        .map(|entry| {
+            let kind = match entry.kind {
+                SearchKind::Port(kind) => kind.to_string(),
+                SearchKind::Switch => "switch".to_owned(),
+                SearchKind::SwitchIp => "switch IP".to_owned(),
+            };
+            vec![
+                entry.text.clone(),
+                kind,
+                entry.detail.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    printtable(&["Match", "Kind", "Details"], &rows);
+}
+
+// Define a function for reading wallsocket descriptions from a file or, for
+// `-`, from standard input. Empty lines and lines starting with `#` are
+// skipped.
+fn readbatch(path: &str) -> io::Result<Vec<String>> {
+    let reader: Box<dyn BufRead> = match path {
+        "-" => Box::new(BufReader::new(io::stdin())),
+        _ => Box::new(BufReader::new(File::open(path)?)),
+    };
+
+    let mut names = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            names.push(line.to_owned());
+        }
+    }
+    Ok(names)
+}
+
+// Define a function for printing the results of a batch lookup as a table,
+// returning the exit code of the first failed lookup.
+fn printbatch(names: &[String], results: &[Result<PortLookup, Error>]) -> Option<i32> {
+    let rows: Vec<Vec<String>> = names
+        .iter()
+        .zip(results)
+        .map(|(name, result)| match result {
+            Ok(lookup) => vec![
+                name.clone(),
+                lookup.switch_label.clone(),
+                lookup.switch_port.clone(),
+                lookup.switch_ip_text(),
+                String::new(),
+            ],
+            Err(error) => vec![
+                name.clone(),
+                String::new(),
+                String::new(),
+                String::new(),
+                // Only the first line, ambiguous matches list their candidates below.
+                error
+                    .to_string()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_owned(),
+            ],
+        })
+        .collect();
+    printtable(&["Wallsocket", "Switch", "Port", "IP", "Error"], &rows);
+
+    results
+        .iter()
+        .find_map(|result| result.as_ref().err())
+        .map(Error::exit_code)
+}
+
+// Define a function for letting the user pick one of the suggested wallsockets.
+fn picksuggestion(suggestions: &[String]) -> Option<String> {
+    println!("No exact match, did you mean:");
+    for (index, suggestion) in suggestions.iter().enumerate() {
+        println!("  {}) {suggestion}", index + 1);
+    }
+    print!("Enter a number or leave empty to quit: ");
+    io::stdout().flush().unwrap();
+
+    let mut choice = String::new();
+    io::stdin().read_line(&mut choice).unwrap();
+    let index: usize = choice.trim().parse().ok()?;
+    suggestions.get(index.checked_sub(1)?).cloned()
+}
+
+// Define a function for printing an error and exiting with its exit code.
+fn fail(error: Error) -> ! {
+    eprintln!("Error: {error}");
+    process::exit(error.exit_code());
+}
+
 // Define the main function.
 fn main() {
+    // Parse the command line arguments.
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Something went wrong: {e}");
+            process::exit(1);
+        }
+    };
+
     // Print a welcome message.
     println!("Welcome to the TDQU-cli:");
 
-    // Connect to the database.
-    let conn = connectdb(None, None, None).expect("Can't connect to database");
+    // Load the configuration and connect to the database, exiting with the
+    // error's exit code on failure.
+    let overrides = ConfigLayer {
+        profile: args.profile.clone(),
+        ..Default::default()
+    };
+    let client = match Config::load_with(overrides).and_then(DcimClient::connect) {
+        Ok(client) => client,
+        Err(error) => fail(error),
+    };
+
+    // If a search was given, list the best matches.
+    if let Some(query) = &args.search {
+        match client.search(query) {
+            Ok(entries) => printsearch(&entries),
+            Err(error) => fail(error),
+        }
+        return;
+    }
+
+    // If a batch file was given, look up every wallsocket listed in it.
+    if let Some(path) = &args.batch {
+        let names = match readbatch(path) {
+            Ok(names) => names,
+            Err(e) => {
+                eprintln!("Could not read {path}: {e}");
+                process::exit(1);
+            }
+        };
+        let refs: Vec<&str> = names.iter().map(String::as_str).collect();
+        match client.lookup_many(&refs) {
+            Ok(results) => {
+                if let Some(code) = printbatch(&names, &results) {
+                    process::exit(code);
+                }
+            }
+            Err(error) => fail(error),
+        }
+        return;
+    }
+
+    // If a switch port was given, look up the wallsocket patched to it.
+    if let (Some(switch), Some(port)) = (&args.switch, &args.port) {
+        match client.reverse_lookup(switch, port) {
+            Ok(lookup) => printlookup(&client, &lookup, args.details),
+            Err(error) => fail(error),
+        }
+        return;
+    }
+
+    // If only a switch was given, list every wallsocket patched to it.
+    if let Some(switch) = &args.switch {
+        match client.switch_sockets(switch) {
+            Ok(lookups) => printsockets(&lookups),
+            Err(error) => fail(error),
+        }
+        return;
+    }
 
     // Get user input.
     let notes = userinput();
 
     // Match on the user input.
     match notes {
+        // If the input is a room, list every wallsocket in it.
+        Ok(n) if n.parse::<RoomId>().is_ok() => match client.room_sockets(&n) {
+            Ok(lookups) => printroom(&lookups),
+            Err(error) => fail(error),
+        },
         // If the input is valid, run a query on the database.
         Ok(n) => {
-            let results = match myquery(conn, &n) {
+            let lookup = match client.lookup(&n) {
                 // If the query is successful, store the results.
-                Ok(rows) => rows,
-                // If there's an error, print it and exit.
-                Err(error) => {
-                    eprintln!("Error: {error}");
-                    return;
+                Ok(lookup) => lookup,
+                // If the wallsocket doesn't exist, offer similar ones.
+                Err(Error::NotFound { query, suggestions }) if !suggestions.is_empty() => {
+                    match picksuggestion(&suggestions) {
+                        Some(notes) => client.lookup(&notes).unwrap_or_else(|e| fail(e)),
+                        None => fail(Error::NotFound {
+                            query,
+                            suggestions: vec![],
+                        }),
+                    }
                 }
+                // If several ports share the description, show all of them.
+                Err(error @ Error::Ambiguous { .. }) => {
+                    if let Error::Ambiguous { candidates, .. } = &error {
+                        for candidate in candidates {
+                            printlookup(&client, candidate, args.details);
+                        }
+                    }
+                    fail(error)
+                }
+                // If there's an error, print it and exit.
+                Err(error) => fail(error),
             };
 
             // Print out the results of the query.
-            println!("\n\t\tHere is what I know:\n");
-            println!("Switchname: {}", results[0]);
-            println!("IP: {}", results[3]);
-            println!("Switchport: {}", results[2]);
-            println!("Description: {}", results[1]);
-            let baseurl = dotenvy::var("DCIMHOST").unwrap();
-            let url: String = format!("https://{}/devices.php?DeviceID={}", baseurl, results[4]);
-            println!("{url}");
+            printlookup(&client, &lookup, args.details);
         }
         // If there's an error with the user input, print it and exit.
         Err(e) => {
             eprintln!("Something went wrong: {e}");
+            process::exit(1);
         }
     }
 }
@@ -0,0 +1,220 @@
+//! The data source the frontends query, independent of where the data comes
+//! from.
+//!
+//! Frontends hold a `Box<dyn DcimBackend>` and never touch the database
+//! themselves. Two implementations ship with this crate: [`MySqlBackend`],
+//! which queries openDCIM's MySQL database, and
+//! [`MemoryBackend`](crate::MemoryBackend), which answers from lookups held
+//! in memory, e.g. an offline snapshot or test fixtures. Other sources like
+//! a REST API only need to implement [`DcimBackend`].
+//!
+//! All methods are `async`. Frontends without a runtime of their own use
+//! the [`blocking::DcimClient`](crate::blocking::DcimClient) adapter instead.
+//!
+//! # Example usage
+//!
+//! ```rust,no_run
+//! use ccntool_core::*;
+//!
+//! async fn print_switch() -> Result<(), Error> {
+//!     let backend = backend::connect(Config::load()?).await?;
+//!     let lookup = backend.lookup("01.1.001-1").await?;
+//!
+//!     println!("{} {}", lookup.switch_label, lookup.switch_port);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    Config, Error, Hop, Identifier, MySqlBackend, PortLookup, SearchEntry, SearchIndex, Uplink,
+};
+use std::{future::Future, pin::Pin};
+
+/// The future returned by the methods of [`DcimBackend`]. The trait stays
+/// object safe, so frontends can pick a backend at runtime.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Everything the frontends ask of openDCIM.
+///
+/// Errors are reported with the variants of [`Error`] closest to what went
+/// wrong, so frontends can handle every backend alike. Methods taking a
+/// switch accept its hostname (`Label` field), IP address (`PrimaryIP`
+/// field) or `DeviceID` from the `fac_Device` table, and fail with
+/// [`Error::MissingSwitch`] if it is blank.
+pub trait DcimBackend: Send + Sync {
+    /// Returns the configuration the backend was created with, e.g. for
+    /// the naming schemes or the openDCIM web URL.
+    fn config(&self) -> &Config;
+
+    /// Looks up the switch port a wallsocket is connected to, following its
+    /// connections through all patch panels.
+    ///
+    /// `name` is looked for among the patch panel ports identified by the
+    /// configured naming schemes. If nothing matches, it is tried in its
+    /// canonical form (see [`SocketId`](crate::SocketId)), so `1.1.1-1`
+    /// finds `01.1.001-1`, and then among ports shared by several outlets,
+    /// so `01.1.001-2` finds the port described as `01.1.001-1,2`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] with the closest existing descriptions as
+    /// suggestions if no matching patch panel port is found,
+    /// [`Error::Ambiguous`] with every traceable path as candidates if
+    /// several matching ports are patched through to a switch, or
+    /// [`Error::BrokenPath`] if the wallsocket isn't patched through to a
+    /// switch.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ccntool_core::*;
+    ///
+    /// # async fn run() -> Result<(), Error> {
+    /// let backend = backend::connect(Config::load()?).await?;
+    /// match backend.lookup("01.1.001-1").await {
+    ///     Ok(lookup) => println!("{} {}", lookup.switch_label, lookup.switch_port),
+    ///     Err(error) => eprintln!("Error: {error}"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<PortLookup, Error>>;
+
+    /// Looks up many wallsockets at once, returning one result per entry of
+    /// `names` in the same order, each being what [`Self::lookup`] would
+    /// have returned for it.
+    ///
+    /// The default implementation looks up one wallsocket after the other,
+    /// backends should read the whole batch at once where they can.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] only if the backend failed as a whole, e.g.
+    /// because the connection is lost.
+    fn lookup_many<'a>(
+        &'a self,
+        names: &'a [&'a str],
+    ) -> BoxFuture<'a, Result<Vec<Result<PortLookup, Error>>, Error>> {
+        Box::pin(async move {
+            let mut results = Vec::with_capacity(names.len());
+            for name in names {
+                results.push(self.lookup(name).await);
+            }
+            Ok(results)
+        })
+    }
+
+    /// The inverse of [`Self::lookup`]: starts at a switch port like
+    /// `Gi1/0/17` and follows its connections backwards through all patch
+    /// panels. The [`PortLookup::wallsocket`] of the result is the
+    /// wallsocket patched to the switch port.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if the switch port doesn't exist or isn't
+    /// patched to a wallsocket, and [`Error::Ambiguous`] if `switch` matches
+    /// more than one device or several members of a stack have a port
+    /// labelled `port`.
+    fn reverse_lookup<'a>(
+        &'a self,
+        switch: &'a str,
+        port: &'a str,
+    ) -> BoxFuture<'a, Result<PortLookup, Error>>;
+
+    /// Lists every wallsocket patched to a switch, ordered by port number,
+    /// e.g. to find out which rooms are affected when the switch goes down.
+    /// Switch ports that are unused or not patched through to a wallsocket
+    /// are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if the switch doesn't exist, and
+    /// [`Error::Ambiguous`] if `switch` matches more than one device.
+    fn switch_sockets<'a>(
+        &'a self,
+        switch: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PortLookup>, Error>>;
+
+    /// Lists every wallsocket in a room like `01.1.001` that is patched to a
+    /// switch, ordered by [`SocketId`](crate::SocketId). Wallsockets that
+    /// aren't patched through to a switch are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidSocket`] if `room` is not a valid room, and
+    /// [`Error::NotFound`] if the room has no wallsockets.
+    fn room_sockets<'a>(&'a self, room: &'a str) -> BoxFuture<'a, Result<Vec<PortLookup>, Error>>;
+
+    /// Follows the uplinks of a switch to the distribution and core layer,
+    /// so second level support knows which switches and uplink ports serve
+    /// it.
+    ///
+    /// Returns the [`Uplink`]s from the switch to the nearest core switch,
+    /// see [`Config::core`], from the bottom up, with parallel uplinks
+    /// between the same two switches all listed. Without a core pattern, or
+    /// if no core switch is reached within [`MAX_UPLINK_LEVELS`] levels,
+    /// only the direct uplinks of the switch are returned, leaving out
+    /// switches connected to nothing but this one, as those hang below it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if the switch doesn't exist, and
+    /// [`Error::Ambiguous`] if `switch` matches more than one device.
+    ///
+    /// [`MAX_UPLINK_LEVELS`]: crate::MAX_UPLINK_LEVELS
+    fn uplinks<'a>(&'a self, switch: &'a str) -> BoxFuture<'a, Result<Vec<Uplink>, Error>>;
+
+    /// Traces the path from a wallsocket to its switch, starting with the
+    /// wallsocket's patch panel port and ending with the switch port. The
+    /// wallsocket is found the same way as by [`Self::lookup`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::lookup`], [`Error::BrokenPath`] is also returned if
+    /// the path passes more than [`MAX_HOPS`](crate::MAX_HOPS) ports.
+    fn trace<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<Hop>, Error>> {
+        Box::pin(async move { Ok(self.lookup(name).await?.path) })
+    }
+
+    /// Lists all valid port identifiers with the kind of the first naming
+    /// scheme they match. Identifiers that are a [`SocketId`](crate::SocketId)
+    /// come first in `SocketId` order, followed by everything else in
+    /// alphabetical order.
+    fn identifiers(&self) -> BoxFuture<'_, Result<Vec<Identifier>, Error>>;
+
+    /// Lists all valid wallsocket descriptions, see [`Self::identifiers`].
+    fn list(&self) -> BoxFuture<'_, Result<Vec<String>, Error>> {
+        Box::pin(async move {
+            let identifiers = self.identifiers().await?;
+
+            Ok(identifiers.into_iter().map(|id| id.name).collect())
+        })
+    }
+
+    /// Loads everything that can be searched for: the port identifiers in
+    /// the order of [`Self::identifiers`], followed by the switches ordered
+    /// by hostname and their IP addresses. The returned [`SearchIndex`] is
+    /// searched in memory, so it can be used to autocomplete input on every
+    /// keystroke.
+    fn search_index(&self) -> BoxFuture<'_, Result<SearchIndex, Error>>;
+
+    /// Searches port identifiers, switch hostnames and switch IP addresses
+    /// for `query`, best match first, see [`SearchIndex::search`]. Frontends
+    /// searching repeatedly should keep the [`Self::search_index`] instead.
+    fn search<'a>(&'a self, query: &'a str) -> BoxFuture<'a, Result<Vec<SearchEntry>, Error>> {
+        Box::pin(async move {
+            let index = self.search_index().await?;
+
+            Ok(index.search(query).into_iter().cloned().collect())
+        })
+    }
+}
+
+/// Connects the backend described by `config`. openDCIM's MySQL database
+/// is the only one reached over the network so far, see
+/// [`MySqlBackend::connect`].
+pub async fn connect(config: Config) -> Result<Box<dyn DcimBackend>, Error> {
+    let backend = MySqlBackend::connect(config).await?;
+
+    Ok(Box::new(backend))
+}
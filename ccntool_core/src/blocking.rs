@@ -0,0 +1,121 @@
+//! Synchronous adapter around any [`DcimBackend`].
+//!
+//! [`DcimClient`] keeps a single threaded tokio runtime next to the backend
+//! and blocks until each query has finished, so frontends without a runtime
+//! of their own can use it. Don't use it from within an async context,
+//! query the [`DcimBackend`] directly instead.
+//!
+//! # Example usage
+//!
+//! ```rust,no_run
+//! use ccntool_core::blocking::DcimClient;
+//!
+//! fn get_all_ports() -> Result<(), ccntool_core::Error> {
+//!     let client = DcimClient::connect(ccntool_core::Config::load()?)?;
+//!     let all_ports = client.list()?;
+//!
+//!     println!("All ports: {:?}", all_ports);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    backend, Config, DcimBackend, Error, Hop, Identifier, PortLookup, SearchEntry, SearchIndex,
+    Uplink,
+};
+use tokio::runtime::{Builder, Runtime};
+
+/// Blocking version of a [`DcimBackend`].
+pub struct DcimClient {
+    runtime: Runtime,
+    backend: Box<dyn DcimBackend>,
+}
+
+impl DcimClient {
+    /// Creates a new `DcimClient` connected to the backend described by
+    /// `config`, see [`backend::connect`].
+    pub fn connect(config: Config) -> Result<Self, Error> {
+        let runtime = runtime()?;
+        let backend = runtime.block_on(backend::connect(config))?;
+
+        Ok(DcimClient { runtime, backend })
+    }
+
+    /// Creates a new `DcimClient` querying `backend`, e.g. a
+    /// [`MemoryBackend`](crate::MemoryBackend).
+    pub fn new(backend: Box<dyn DcimBackend>) -> Result<Self, Error> {
+        Ok(DcimClient {
+            runtime: runtime()?,
+            backend,
+        })
+    }
+
+    /// Returns the configuration the backend was created with.
+    pub fn config(&self) -> &Config {
+        self.backend.config()
+    }
+
+    /// Blocking version of [`DcimBackend::lookup`].
+    pub fn lookup(&self, name: &str) -> Result<PortLookup, Error> {
+        self.runtime.block_on(self.backend.lookup(name))
+    }
+
+    /// Blocking version of [`DcimBackend::lookup_many`].
+    pub fn lookup_many(&self, names: &[&str]) -> Result<Vec<Result<PortLookup, Error>>, Error> {
+        self.runtime.block_on(self.backend.lookup_many(names))
+    }
+
+    /// Blocking version of [`DcimBackend::reverse_lookup`].
+    pub fn reverse_lookup(&self, switch: &str, port: &str) -> Result<PortLookup, Error> {
+        self.runtime
+            .block_on(self.backend.reverse_lookup(switch, port))
+    }
+
+    /// Blocking version of [`DcimBackend::switch_sockets`].
+    pub fn switch_sockets(&self, switch: &str) -> Result<Vec<PortLookup>, Error> {
+        self.runtime.block_on(self.backend.switch_sockets(switch))
+    }
+
+    /// Blocking version of [`DcimBackend::room_sockets`].
+    pub fn room_sockets(&self, room: &str) -> Result<Vec<PortLookup>, Error> {
+        self.runtime.block_on(self.backend.room_sockets(room))
+    }
+
+    /// Blocking version of [`DcimBackend::uplinks`].
+    pub fn uplinks(&self, switch: &str) -> Result<Vec<Uplink>, Error> {
+        self.runtime.block_on(self.backend.uplinks(switch))
+    }
+
+    /// Blocking version of [`DcimBackend::trace`].
+    pub fn trace(&self, name: &str) -> Result<Vec<Hop>, Error> {
+        self.runtime.block_on(self.backend.trace(name))
+    }
+
+    /// Blocking version of [`DcimBackend::identifiers`].
+    pub fn identifiers(&self) -> Result<Vec<Identifier>, Error> {
+        self.runtime.block_on(self.backend.identifiers())
+    }
+
+    /// Blocking version of [`DcimBackend::list`].
+    pub fn list(&self) -> Result<Vec<String>, Error> {
+        self.runtime.block_on(self.backend.list())
+    }
+
+    /// Blocking version of [`DcimBackend::search_index`].
+    pub fn search_index(&self) -> Result<SearchIndex, Error> {
+        self.runtime.block_on(self.backend.search_index())
+    }
+
+    /// Blocking version of [`DcimBackend::search`].
+    pub fn search(&self, query: &str) -> Result<Vec<SearchEntry>, Error> {
+        self.runtime.block_on(self.backend.search(query))
+    }
+}
+
+/// Builds the runtime the queries are run on.
+fn runtime() -> Result<Runtime, Error> {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|error| Error::Connection(sqlx::Error::Io(error)))
+}
@@ -0,0 +1,702 @@
+//! Layered configuration for connecting to openDCIM.
+//!
+//! The configuration is merged from the following sources, each one
+//! overriding the values of the ones before it:
+//!
+//! 1. built-in defaults (port `3306`, database `dcim`)
+//! 2. the TOML config file, see [`Config::path`]
+//! 3. the selected profile from the config file
+//! 4. a `.env` file in the current directory or one of its parents
+//! 5. environment variables
+//! 6. explicit overrides passed by the frontend
+//!
+//! Credentials still missing afterwards are taken from a secret file, a
+//! password command or the MySQL option file, see [`Config::load_with`].
+//!
+//! # Config file
+//!
+//! ```toml
+//! host = "dcim.my.tld"
+//! username = "user"
+//! web_url = "https://dcim.my.tld"
+//! # profile used if none is given explicitly or via DCIMPROFILE
+//! profile = "campus"
+//!
+//! [profiles.campus]
+//! database = "dcim_campus"
+//!
+//! [profiles.test]
+//! host = "dcim-test.my.tld"
+//! port = 3307
+//! ssl_mode = "verify-identity"
+//! ssl_ca = "/etc/ssl/certs/campus-ca.pem"
+//! ```
+//!
+//! Naming schemes for the port identifiers are set as `[[naming]]` tables
+//! in the config file or a profile, see [`crate::naming`].
+//!
+//! Core switches are recognised by a MySQL regular expression matched
+//! against their hostname, e.g. `core = '^(core|csw)-'`. Without it, the
+//! uplink chain of a switch ends at its direct uplinks, see
+//! [`DcimBackend::uplinks`](crate::DcimBackend::uplinks).
+//!
+//! # Environment variables
+//!
+//! | Variable              | Field              |
+//! |-----------------------|--------------------|
+//! | `DCIMCONFIG`          | config file path   |
+//! | `DCIMPROFILE`         | `profile`          |
+//! | `DCIMHOST`            | `host`             |
+//! | `DCIMPORT`            | `port`             |
+//! | `DCIMDATABASE`        | `database`         |
+//! | `DCIMUSER`            | `username`         |
+//! | `DCIMPASSWORD`        | `password`         |
+//! | `DCIMPASSWORDFILE`    | `password_file`    |
+//! | `DCIMPASSWORDCOMMAND` | `password_command` |
+//! | `DCIMOPTIONFILE`      | `option_file`      |
+//! | `DCIMWEBURL`          | `web_url`          |
+//! | `DCIMSSLMODE`         | `ssl_mode`         |
+//! | `DCIMSSLCA`           | `ssl_ca`           |
+//! | `DCIMSSLCERT`         | `ssl_cert`         |
+//! | `DCIMSSLKEY`          | `ssl_key`          |
+//! | `DCIMCORE`            | `core`             |
+
+use crate::{credentials, naming::NamingScheme, Error};
+use serde::{Deserialize, Serialize};
+use sqlx::mysql::{MySqlConnectOptions, MySqlSslMode};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Default port of the MySQL server.
+pub const DEFAULT_PORT: u16 = 3306;
+
+/// Default name of the openDCIM database.
+pub const DEFAULT_DATABASE: &str = "dcim";
+
+/// Whether and how the connection to the MySQL server is encrypted, see
+/// the `--ssl-mode` option of the MySQL client.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    /// Never encrypt the connection.
+    Disabled,
+    /// Encrypt the connection if the server supports it.
+    #[default]
+    Preferred,
+    /// Always encrypt the connection, without verifying the certificate.
+    Required,
+    /// Always encrypt the connection and verify the server certificate
+    /// against the CA.
+    VerifyCa,
+    /// Like `VerifyCa`, and additionally verify that the certificate was
+    /// issued for the configured host.
+    VerifyIdentity,
+}
+
+impl FromStr for SslMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "disabled" => Ok(SslMode::Disabled),
+            "preferred" => Ok(SslMode::Preferred),
+            "required" => Ok(SslMode::Required),
+            "verify-ca" => Ok(SslMode::VerifyCa),
+            "verify-identity" => Ok(SslMode::VerifyIdentity),
+            _ => Err(Error::Config(format!("unknown ssl mode \"{s}\""))),
+        }
+    }
+}
+
+impl From<SslMode> for MySqlSslMode {
+    fn from(mode: SslMode) -> Self {
+        match mode {
+            SslMode::Disabled => MySqlSslMode::Disabled,
+            SslMode::Preferred => MySqlSslMode::Preferred,
+            SslMode::Required => MySqlSslMode::Required,
+            SslMode::VerifyCa => MySqlSslMode::VerifyCa,
+            SslMode::VerifyIdentity => MySqlSslMode::VerifyIdentity,
+        }
+    }
+}
+
+/// A single layer of configuration values, every one of them optional.
+///
+/// This is the format of the config file and its profiles as well as the
+/// way frontends pass explicit overrides to [`Config::load_with`].
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ConfigLayer {
+    /// Name of the profile to apply on top of the config file.
+    pub profile: Option<String>,
+    /// Hostname of the MySQL server.
+    pub host: Option<String>,
+    /// Port of the MySQL server.
+    pub port: Option<u16>,
+    /// Name of the openDCIM database.
+    pub database: Option<String>,
+    /// Username for the MySQL server.
+    pub username: Option<String>,
+    /// Password for the MySQL server.
+    pub password: Option<String>,
+    /// File containing the password, e.g. a container secret.
+    pub password_file: Option<PathBuf>,
+    /// Shell command printing the password, e.g. `pass show dcim`.
+    pub password_command: Option<String>,
+    /// MySQL option file to read `user` and `password` from, defaults to
+    /// `~/.my.cnf`.
+    pub option_file: Option<PathBuf>,
+    /// Base URL of the openDCIM web interface.
+    pub web_url: Option<String>,
+    /// Whether and how the connection is encrypted.
+    pub ssl_mode: Option<SslMode>,
+    /// Path to a PEM file with the CA certificates to verify the server with.
+    pub ssl_ca: Option<PathBuf>,
+    /// Path to a PEM file with the client certificate.
+    pub ssl_cert: Option<PathBuf>,
+    /// Path to a PEM file with the private key of the client certificate.
+    pub ssl_key: Option<PathBuf>,
+    /// Naming schemes of the port identifiers, replacing those of lower
+    /// layers as a whole.
+    pub naming: Option<Vec<NamingScheme>>,
+    /// MySQL regular expression matching the hostnames of core switches.
+    pub core: Option<String>,
+}
+
+impl fmt::Debug for ConfigLayer {
+    /// Formats the layer without revealing the password.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConfigLayer")
+            .field("profile", &self.profile)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("database", &self.database)
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("password_file", &self.password_file)
+            .field("password_command", &self.password_command)
+            .field("option_file", &self.option_file)
+            .field("web_url", &self.web_url)
+            .field("ssl_mode", &self.ssl_mode)
+            .field("ssl_ca", &self.ssl_ca)
+            .field("ssl_cert", &self.ssl_cert)
+            .field("ssl_key", &self.ssl_key)
+            .field("naming", &self.naming)
+            .field("core", &self.core)
+            .finish()
+    }
+}
+
+impl ConfigLayer {
+    /// Reads a layer from the `DCIM*` variables yielded by `vars`.
+    fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Result<Self, Error> {
+        let mut layer = ConfigLayer::default();
+        for (key, value) in vars {
+            match key.as_str() {
+                "DCIMPROFILE" => layer.profile = Some(value),
+                "DCIMHOST" => layer.host = Some(value),
+                "DCIMPORT" => {
+                    let port = value
+                        .parse()
+                        .map_err(|_| Error::Config(format!("DCIMPORT is not a port: {value}")))?;
+                    layer.port = Some(port);
+                }
+                "DCIMDATABASE" => layer.database = Some(value),
+                "DCIMUSER" => layer.username = Some(value),
+                "DCIMPASSWORD" => layer.password = Some(value),
+                "DCIMPASSWORDFILE" => layer.password_file = Some(value.into()),
+                "DCIMPASSWORDCOMMAND" => layer.password_command = Some(value),
+                "DCIMOPTIONFILE" => layer.option_file = Some(value.into()),
+                "DCIMWEBURL" => layer.web_url = Some(value),
+                "DCIMSSLMODE" => layer.ssl_mode = Some(value.parse()?),
+                "DCIMSSLCA" => layer.ssl_ca = Some(value.into()),
+                "DCIMSSLCERT" => layer.ssl_cert = Some(value.into()),
+                "DCIMSSLKEY" => layer.ssl_key = Some(value.into()),
+                "DCIMCORE" => layer.core = Some(value),
+                _ => {}
+            }
+        }
+
+        Ok(layer)
+    }
+
+    /// Overwrites all values of `self` which are set in `other`.
+    fn merge(&mut self, other: ConfigLayer) {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field;
+                })*
+            };
+        }
+
+        merge!(
+            profile,
+            host,
+            port,
+            database,
+            username,
+            password,
+            password_file,
+            password_command,
+            option_file,
+            web_url,
+            ssl_mode,
+            ssl_ca,
+            ssl_cert,
+            ssl_key,
+            naming,
+            core
+        );
+    }
+}
+
+/// Stands in for a password in `Debug` output.
+fn redacted(password: &Option<String>) -> Option<&'static str> {
+    password.as_ref().map(|_| "<redacted>")
+}
+
+/// Contents of the TOML config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    #[serde(flatten)]
+    base: ConfigLayer,
+    profiles: BTreeMap<String, ConfigLayer>,
+}
+
+/// The merged configuration used to connect to openDCIM.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Name of the applied profile, if any.
+    pub profile: Option<String>,
+    /// Hostname of the MySQL server.
+    pub host: Option<String>,
+    /// Port of the MySQL server.
+    pub port: u16,
+    /// Name of the openDCIM database.
+    pub database: String,
+    /// Username for the MySQL server.
+    pub username: Option<String>,
+    /// Password for the MySQL server.
+    pub password: Option<String>,
+    /// File containing the password, e.g. a container secret.
+    pub password_file: Option<PathBuf>,
+    /// Shell command printing the password, e.g. `pass show dcim`.
+    pub password_command: Option<String>,
+    /// MySQL option file to read `user` and `password` from, defaults to
+    /// `~/.my.cnf`.
+    pub option_file: Option<PathBuf>,
+    /// Base URL of the openDCIM web interface, defaults to `https://<host>`.
+    pub web_url: Option<String>,
+    /// Whether and how the connection is encrypted.
+    pub ssl_mode: SslMode,
+    /// Path to a PEM file with the CA certificates to verify the server with.
+    pub ssl_ca: Option<PathBuf>,
+    /// Path to a PEM file with the client certificate.
+    pub ssl_cert: Option<PathBuf>,
+    /// Path to a PEM file with the private key of the client certificate.
+    pub ssl_key: Option<PathBuf>,
+    /// Naming schemes of the port identifiers, defaults to
+    /// [`NamingScheme::hsd`].
+    pub naming: Vec<NamingScheme>,
+    /// MySQL regular expression matching the hostnames of core switches.
+    pub core: Option<String>,
+}
+
+impl fmt::Debug for Config {
+    /// Formats the configuration without revealing the password.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("profile", &self.profile)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("database", &self.database)
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("password_file", &self.password_file)
+            .field("password_command", &self.password_command)
+            .field("option_file", &self.option_file)
+            .field("web_url", &self.web_url)
+            .field("ssl_mode", &self.ssl_mode)
+            .field("ssl_ca", &self.ssl_ca)
+            .field("ssl_cert", &self.ssl_cert)
+            .field("ssl_key", &self.ssl_key)
+            .field("naming", &self.naming)
+            .field("core", &self.core)
+            .finish()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            profile: None,
+            host: None,
+            port: DEFAULT_PORT,
+            database: DEFAULT_DATABASE.to_owned(),
+            username: None,
+            password: None,
+            password_file: None,
+            password_command: None,
+            option_file: None,
+            web_url: None,
+            ssl_mode: SslMode::default(),
+            ssl_ca: None,
+            ssl_cert: None,
+            ssl_key: None,
+            naming: NamingScheme::hsd(),
+            core: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the configuration from all sources without explicit overrides.
+    pub fn load() -> Result<Self, Error> {
+        Config::load_with(ConfigLayer::default())
+    }
+
+    /// Loads the configuration from all sources, applying `overrides` last.
+    ///
+    /// If no password is set afterwards, it is read from `password_file`,
+    /// then from the output of `password_command` and finally from the
+    /// `[client]` section of the MySQL option file, which also provides the
+    /// username if none is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if the config file can't be read or parsed,
+    /// if the selected profile doesn't exist or if a credential provider
+    /// fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use ccntool_core::config::{Config, ConfigLayer};
+    ///
+    /// let config = Config::load_with(ConfigLayer {
+    ///     profile: Some("test".to_owned()),
+    ///     ..Default::default()
+    /// })?;
+    /// # Ok::<(), ccntool_core::Error>(())
+    /// ```
+    pub fn load_with(overrides: ConfigLayer) -> Result<Self, Error> {
+        let envfile = match dotenvy::dotenv_iter() {
+            Ok(iter) => ConfigLayer::from_vars(iter.filter_map(Result::ok))?,
+            Err(_) => ConfigLayer::default(),
+        };
+        let env = ConfigLayer::from_vars(std::env::vars())?;
+
+        let path = std::env::var_os("DCIMCONFIG")
+            .map(PathBuf::from)
+            .or_else(Config::path);
+        let file = match path {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(contents) => toml::from_str(&contents)
+                    .map_err(|error| Error::Config(format!("{}: {error}", path.display())))?,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => ConfigFile::default(),
+                Err(error) => return Err(Error::Config(format!("{}: {error}", path.display()))),
+            },
+            None => ConfigFile::default(),
+        };
+
+        let mut config = Config::merge(file, envfile, env, overrides)?;
+        credentials::resolve(&mut config)?;
+
+        Ok(config)
+    }
+
+    /// Default location of the config file, `ccntool/config.toml` inside the
+    /// platform's config directory (`$XDG_CONFIG_HOME` on Linux).
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("ccntool").join("config.toml"))
+    }
+
+    /// Merges all layers in order of precedence.
+    fn merge(
+        mut file: ConfigFile,
+        envfile: ConfigLayer,
+        env: ConfigLayer,
+        overrides: ConfigLayer,
+    ) -> Result<Self, Error> {
+        // The profile is picked from the highest layer naming one.
+        let profile = overrides
+            .profile
+            .clone()
+            .or_else(|| env.profile.clone())
+            .or_else(|| envfile.profile.clone())
+            .or_else(|| file.base.profile.clone());
+
+        let mut layer = file.base;
+        if let Some(name) = &profile {
+            let selected = file
+                .profiles
+                .remove(name)
+                .ok_or_else(|| Error::Config(format!("unknown profile \"{name}\"")))?;
+            layer.merge(selected);
+        }
+        layer.merge(envfile);
+        layer.merge(env);
+        layer.merge(overrides);
+
+        let defaults = Config::default();
+        Ok(Config {
+            profile,
+            host: layer.host,
+            port: layer.port.unwrap_or(defaults.port),
+            database: layer.database.unwrap_or(defaults.database),
+            username: layer.username,
+            password: layer.password,
+            password_file: layer.password_file,
+            password_command: layer.password_command,
+            option_file: layer.option_file,
+            web_url: layer.web_url,
+            ssl_mode: layer.ssl_mode.unwrap_or(defaults.ssl_mode),
+            ssl_ca: layer.ssl_ca,
+            ssl_cert: layer.ssl_cert,
+            ssl_key: layer.ssl_key,
+            naming: layer.naming.unwrap_or(defaults.naming),
+            core: layer.core,
+        })
+    }
+
+    /// Builds the options for connecting to the MySQL server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if the host, username or password is
+    /// missing, if only one of `ssl_cert` and `ssl_key` is set or if one of
+    /// the certificate files doesn't exist.
+    pub fn connect_options(&self) -> Result<MySqlConnectOptions, Error> {
+        let host = self
+            .host
+            .as_deref()
+            .ok_or_else(|| missing("host", "DCIMHOST"))?;
+        let username = self
+            .username
+            .as_deref()
+            .ok_or_else(|| missing("username", "DCIMUSER"))?;
+        let password = self
+            .password
+            .as_deref()
+            .ok_or_else(|| missing("password", "DCIMPASSWORD"))?;
+
+        let mut options = MySqlConnectOptions::new()
+            .host(host)
+            .port(self.port)
+            .database(&self.database)
+            .username(username)
+            .password(password)
+            .ssl_mode(self.ssl_mode.into());
+
+        if let Some(ca) = &self.ssl_ca {
+            options = options.ssl_ca(existing("ssl_ca", ca)?);
+        }
+
+        match (&self.ssl_cert, &self.ssl_key) {
+            (Some(cert), Some(key)) => {
+                options = options
+                    .ssl_client_cert(existing("ssl_cert", cert)?)
+                    .ssl_client_key(existing("ssl_key", key)?);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(Error::Config(
+                    "ssl_cert and ssl_key have to be set together".to_owned(),
+                ))
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Returns the link to a device in the openDCIM web interface.
+    pub fn device_url(&self, device_id: i32) -> String {
+        let base = match (&self.web_url, &self.host) {
+            (Some(web_url), _) => web_url.trim_end_matches('/').to_owned(),
+            (None, Some(host)) => format!("https://{host}"),
+            (None, None) => String::new(),
+        };
+
+        format!("{base}/devices.php?DeviceID={device_id}")
+    }
+}
+
+/// Makes sure the file configured as `field` exists.
+fn existing<'a>(field: &str, path: &'a Path) -> Result<&'a Path, Error> {
+    match path.is_file() {
+        true => Ok(path),
+        false => Err(Error::Config(format!(
+            "{field} file {} doesn't exist",
+            path.display()
+        ))),
+    }
+}
+
+/// Builds the error for a missing configuration value.
+fn missing(field: &str, var: &str) -> Error {
+    Error::Config(format!(
+        "no {field} configured, set {var} or `{field}` in the config file"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_password() {
+        let layer = ConfigLayer {
+            username: Some("dcim".to_owned()),
+            password: Some("hunter2".to_owned()),
+            ..Default::default()
+        };
+        let config = Config {
+            password: layer.password.clone(),
+            ..Default::default()
+        };
+
+        for debug in [format!("{layer:?}"), format!("{config:?}")] {
+            assert!(!debug.contains("hunter2"), "{debug}");
+            assert!(debug.contains("<redacted>"), "{debug}");
+        }
+        assert!(format!("{layer:?}").contains("dcim"));
+    }
+
+    fn file(toml: &str) -> ConfigFile {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn layer(vars: &[(&str, &str)]) -> ConfigLayer {
+        ConfigLayer::from_vars(
+            vars.iter()
+                .map(|(key, value)| (key.to_string(), value.to_string())),
+        )
+        .unwrap()
+    }
+
+    const FILE: &str = r#"
+host = "file"
+database = "dcim_file"
+username = "file"
+core = "^core-"
+
+[profiles.test]
+host = "profile"
+port = 3307
+ssl_mode = "verify-identity"
+
+[profiles.other]
+database = "dcim_other"
+"#;
+
+    #[test]
+    fn defaults_apply_without_layers() {
+        let config = Config::merge(
+            ConfigFile::default(),
+            ConfigLayer::default(),
+            ConfigLayer::default(),
+            ConfigLayer::default(),
+        )
+        .unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.port, 3306);
+        assert_eq!(config.database, "dcim");
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let config = Config::merge(
+            file(FILE),
+            layer(&[("DCIMHOST", "envfile"), ("DCIMUSER", "envfile")]),
+            layer(&[("DCIMHOST", "env"), ("DCIMPROFILE", "test")]),
+            ConfigLayer {
+                host: Some("override".to_owned()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(config.profile.as_deref(), Some("test"));
+        assert_eq!(config.host.as_deref(), Some("override"));
+        assert_eq!(config.username.as_deref(), Some("envfile"));
+        assert_eq!(config.port, 3307);
+        assert_eq!(config.ssl_mode, SslMode::VerifyIdentity);
+        assert_eq!(config.database, "dcim_file");
+        assert_eq!(config.core.as_deref(), Some("^core-"));
+    }
+
+    #[test]
+    fn profile_is_picked_from_the_highest_layer() {
+        let config = Config::merge(
+            file(&format!("profile = \"test\"\n{FILE}")),
+            ConfigLayer::default(),
+            layer(&[("DCIMPROFILE", "other")]),
+            ConfigLayer::default(),
+        )
+        .unwrap();
+        assert_eq!(config.profile.as_deref(), Some("other"));
+        assert_eq!(config.host.as_deref(), Some("file"));
+        assert_eq!(config.database, "dcim_other");
+    }
+
+    #[test]
+    fn unknown_profile_is_an_error() {
+        let result = Config::merge(
+            file(FILE),
+            ConfigLayer::default(),
+            ConfigLayer::default(),
+            ConfigLayer {
+                profile: Some("missing".to_owned()),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn naming_is_replaced_as_a_whole() {
+        let config = Config::merge(
+            file(
+                r#"
+[[naming]]
+pattern = "^AP"
+kind = "access-point"
+"#,
+            ),
+            ConfigLayer::default(),
+            ConfigLayer::default(),
+            ConfigLayer::default(),
+        )
+        .unwrap();
+        assert_eq!(config.naming.len(), 1);
+        assert_eq!(config.naming[0].pattern, "^AP");
+    }
+
+    #[test]
+    fn invalid_variables_are_errors() {
+        let vars = |key: &str, value: &str| [(key.to_owned(), value.to_owned())];
+        assert!(ConfigLayer::from_vars(vars("DCIMPORT", "abc")).is_err());
+        assert!(ConfigLayer::from_vars(vars("DCIMSSLMODE", "sometimes")).is_err());
+        assert!(ConfigLayer::from_vars(vars("OTHER", "ignored")).is_ok());
+    }
+
+    #[test]
+    fn device_url_falls_back_to_host() {
+        let mut config = Config {
+            host: Some("dcim.my.tld".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.device_url(7),
+            "https://dcim.my.tld/devices.php?DeviceID=7"
+        );
+        config.web_url = Some("http://dcim/".to_owned());
+        assert_eq!(config.device_url(7), "http://dcim/devices.php?DeviceID=7");
+    }
+}
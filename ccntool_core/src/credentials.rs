@@ -0,0 +1,217 @@
+//! Credential providers filling in what the configuration layers left open.
+//!
+//! The password is taken from the first of these providing one:
+//!
+//! 1. `password` from the config file, `.env`, environment or frontend
+//! 2. the contents of `password_file`
+//! 3. the output of `password_command`
+//! 4. `password` in the `[client]` section of the MySQL option file
+//!
+//! The username falls back to `user` in the MySQL option file as well.
+
+use crate::{Config, Error};
+use std::{fs, io, path::Path, process::Command};
+
+/// Fills in the username and password of `config` from the credential
+/// providers, if they are not set already.
+pub(crate) fn resolve(config: &mut Config) -> Result<(), Error> {
+    if config.password.is_none() {
+        if let Some(path) = &config.password_file {
+            config.password = Some(read_secret(path)?);
+        }
+    }
+
+    if config.password.is_none() {
+        if let Some(command) = &config.password_command {
+            config.password = Some(run_command(command)?);
+        }
+    }
+
+    if config.username.is_none() || config.password.is_none() {
+        let path = config.option_file.clone().or_else(default_option_file);
+        if let Some(path) = path {
+            let (user, password) = read_option_file(&path)?;
+            config.username = config.username.take().or(user);
+            config.password = config.password.take().or(password);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a secret file, as mounted by Docker or Kubernetes, without its
+/// trailing newline.
+fn read_secret(path: &Path) -> Result<String, Error> {
+    let secret = fs::read_to_string(path)
+        .map_err(|error| Error::Config(format!("password_file {}: {error}", path.display())))?;
+
+    Ok(secret.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// Runs `command` in the system shell and returns the first line of its
+/// output, like `pass show dcim` does.
+fn run_command(command: &str) -> Result<String, Error> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|error| Error::Config(format!("password_command \"{command}\": {error}")))?;
+
+    if !output.status.success() {
+        return Err(Error::Config(format!(
+            "password_command \"{command}\" failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().to_owned())
+}
+
+/// Default location of the MySQL option file, `~/.my.cnf`.
+fn default_option_file() -> Option<std::path::PathBuf> {
+    dirs::home_dir().map(|home| home.join(".my.cnf"))
+}
+
+/// Reads `user` and `password` from the `[client]` section of a MySQL option
+/// file. A missing file is not an error.
+fn read_option_file(path: &Path) -> Result<(Option<String>, Option<String>), Error> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok((None, None)),
+        Err(error) => return Err(Error::Config(format!("{}: {error}", path.display()))),
+    };
+
+    let mut user = None;
+    let mut password = None;
+    let mut in_client = false;
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(['#', ';', '!']) {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_client = section.trim() == "client";
+            continue;
+        }
+        if !in_client {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let value = unquote(value.trim());
+            match key.trim().replace('-', "_").as_str() {
+                "user" => user = Some(value),
+                "password" => password = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    Ok((user, password))
+}
+
+/// Strips matching single or double quotes around an option value.
+fn unquote(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner.to_owned();
+        }
+    }
+
+    value.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Writes `contents` to a file in the temporary directory, unique per
+    /// test.
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ccntool-{}-{name}", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn option_file_reads_client_section() {
+        let path = temp_file(
+            "my.cnf",
+            r#"
+# comment
+[mysqld]
+user = server
+password = nope
+
+[client]
+user = "dcim"
+; comment
+password='se=cret'
+host = ignored
+
+[mysql]
+password = other
+"#,
+        );
+        let (user, password) = read_option_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(user.as_deref(), Some("dcim"));
+        assert_eq!(password.as_deref(), Some("se=cret"));
+    }
+
+    #[test]
+    fn missing_option_file_is_no_error() {
+        let path = std::env::temp_dir().join("ccntool-does-not-exist.cnf");
+        assert_eq!(read_option_file(&path).unwrap(), (None, None));
+    }
+
+    #[test]
+    fn unquote_strips_matching_quotes_only() {
+        assert_eq!(unquote("\"a b\""), "a b");
+        assert_eq!(unquote("'a'"), "a");
+        assert_eq!(unquote("\"a'"), "\"a'");
+        assert_eq!(unquote("a"), "a");
+    }
+
+    #[test]
+    fn secret_file_loses_trailing_newline() {
+        let path = temp_file("secret", "hunter2\r\n");
+        let secret = read_secret(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(secret, "hunter2");
+    }
+
+    #[test]
+    fn providers_apply_in_order() {
+        let secret = temp_file("order-secret", "from-file\n");
+        let option_file = temp_file("order.cnf", "[client]\nuser=cnf\npassword=from-cnf\n");
+        let mut config = Config {
+            password_file: Some(secret.clone()),
+            password_command: Some("echo from-command".to_owned()),
+            option_file: Some(option_file.clone()),
+            ..Default::default()
+        };
+        resolve(&mut config).unwrap();
+        assert_eq!(config.username.as_deref(), Some("cnf"));
+        assert_eq!(config.password.as_deref(), Some("from-file"));
+
+        config.password = None;
+        config.password_file = None;
+        resolve(&mut config).unwrap();
+        assert_eq!(config.password.as_deref(), Some("from-command"));
+
+        config.password = Some("explicit".to_owned());
+        resolve(&mut config).unwrap();
+        assert_eq!(config.password.as_deref(), Some("explicit"));
+
+        fs::remove_file(&secret).unwrap();
+        fs::remove_file(&option_file).unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+//! Hardware, template and ownership details of a device, for escalating
+//! problems to whoever is responsible for a switch.
+
+use serde::{Deserialize, Serialize};
+
+/// Hardware, template and ownership details of a device.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeviceDetails {
+    /// Manufacturer (`Name` field from `fac_Manufacturer` table)
+    pub manufacturer: String,
+    /// Model (`Model` field from `fac_DeviceTemplate` table)
+    pub model: String,
+    /// Serial number (`SerialNo` field from `fac_Device` table)
+    pub serial_no: String,
+    /// Asset tag (`AssetTag` field from `fac_Device` table)
+    pub asset_tag: String,
+    /// Installation date as `YYYY-MM-DD` (`InstallDate` field from
+    /// `fac_Device` table), `None` if not set
+    pub install_date: Option<String>,
+    /// End of warranty as `YYYY-MM-DD` (`WarrantyExpire` field from
+    /// `fac_Device` table), `None` if not set
+    pub warranty_expire: Option<String>,
+    /// Owning department (`Name` field from `fac_Department` table)
+    pub department: String,
+    /// Primary contact (`fac_Contact` or, since openDCIM 4.3, `fac_People`
+    /// table), `None` if not set
+    pub contact: Option<Contact>,
+}
+
+/// A person responsible for a device.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Contact {
+    /// First and last name (`FirstName` and `LastName` fields from
+    /// `fac_Contact` or `fac_People` table)
+    pub name: String,
+    /// Phone number (`Phone1` field from `fac_Contact` or `fac_People`
+    /// table)
+    pub phone: String,
+    /// Email address (`Email` field from `fac_Contact` or `fac_People`
+    /// table)
+    pub email: String,
+}
+
+impl DeviceDetails {
+    /// Returns every detail that is set as a label and its value, in
+    /// display order, so all frontends show the same.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("Manufacturer", self.manufacturer.clone()),
+            ("Model", self.model.clone()),
+            ("Serial number", self.serial_no.clone()),
+            ("Asset tag", self.asset_tag.clone()),
+            ("Installed", self.install_date.clone().unwrap_or_default()),
+            (
+                "Warranty until",
+                self.warranty_expire.clone().unwrap_or_default(),
+            ),
+            ("Department", self.department.clone()),
+        ];
+        if let Some(contact) = &self.contact {
+            fields.push(("Contact", contact.name.clone()));
+            fields.push(("Phone", contact.phone.clone()));
+            fields.push(("Email", contact.email.clone()));
+        }
+        fields.retain(|(_, value)| !value.is_empty());
+
+        fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_are_empty_without_details() {
+        assert!(DeviceDetails::default().fields().is_empty());
+    }
+
+    #[test]
+    fn fields_leave_out_empty_and_missing_values() {
+        let details = DeviceDetails {
+            model: "C9300-48P".to_owned(),
+            install_date: Some("2021-03-01".to_owned()),
+            warranty_expire: None,
+            contact: Some(Contact {
+                email: "noc@example.org".to_owned(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            details.fields(),
+            [
+                ("Model", "C9300-48P".to_owned()),
+                ("Installed", "2021-03-01".to_owned()),
+                ("Email", "noc@example.org".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fields_keep_the_display_order() {
+        let details = DeviceDetails {
+            manufacturer: "Cisco".to_owned(),
+            model: "C9300-48P".to_owned(),
+            serial_no: "FOC1234X0AB".to_owned(),
+            asset_tag: "4711".to_owned(),
+            install_date: Some("2021-03-01".to_owned()),
+            warranty_expire: Some("2026-03-01".to_owned()),
+            department: "IT".to_owned(),
+            contact: Some(Contact {
+                name: "Jane Doe".to_owned(),
+                phone: "+49 211 4351".to_owned(),
+                email: "noc@example.org".to_owned(),
+            }),
+        };
+
+        let labels: Vec<&str> = details
+            .fields()
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "Manufacturer",
+                "Model",
+                "Serial number",
+                "Asset tag",
+                "Installed",
+                "Warranty until",
+                "Department",
+                "Contact",
+                "Phone",
+                "Email"
+            ]
+        );
+    }
+}
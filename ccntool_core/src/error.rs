@@ -0,0 +1,209 @@
+use crate::PortLookup;
+use sqlx::mysql::MySqlDatabaseError;
+
+/// Represents an error that occurred while talking to the openDCIM database.
+///
+/// Every variant carries a human-readable message which can be shown to the
+/// user as is, and maps to a distinct process exit code via
+/// [`Error::exit_code`].
+#[derive(Debug)]
+pub enum Error {
+    /// A required configuration value is missing or invalid.
+    Config(String),
+    /// The database server could not be reached or the connection broke.
+    Connection(sqlx::Error),
+    /// The database server rejected the supplied credentials.
+    Authentication(String),
+    /// The TLS handshake failed, e.g. because the server certificate could
+    /// not be verified.
+    Tls(String),
+    /// The database server did not answer in time.
+    Timeout,
+    /// No wallsocket, switch or port matches the given description. For
+    /// wallsockets, `suggestions` holds the closest existing descriptions.
+    NotFound {
+        query: String,
+        suggestions: Vec<String>,
+    },
+    /// The path from the wallsocket doesn't end at a switch, e.g. because
+    /// a patch cable is missing or the connections form a loop.
+    BrokenPath(String),
+    /// More than one wallsocket, switch or port matches the given
+    /// description. For wallsockets, `candidates` holds the lookup of every
+    /// matching port that is patched through to a switch.
+    Ambiguous {
+        query: String,
+        count: usize,
+        candidates: Vec<PortLookup>,
+    },
+    /// The database does not look like an openDCIM database.
+    Schema(String),
+    /// Any other error reported by the database server.
+    Database(sqlx::Error),
+    /// The input is not a valid wallsocket identifier, see
+    /// [`crate::SocketId`].
+    InvalidSocket(String),
+    /// The switch to look up is empty.
+    MissingSwitch,
+}
+
+impl Error {
+    /// Builds an [`Error::NotFound`] without suggestions.
+    pub(crate) fn not_found(query: impl Into<String>) -> Self {
+        Error::NotFound {
+            query: query.into(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Returns the process exit code frontends should use for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Config(_) => 2,
+            Error::Connection(_) => 3,
+            Error::Authentication(_) => 4,
+            Error::Timeout => 5,
+            Error::NotFound { .. } => 6,
+            Error::Ambiguous { .. } => 7,
+            Error::Schema(_) => 8,
+            Error::Database(_) => 9,
+            Error::Tls(_) => 10,
+            Error::BrokenPath(_) => 11,
+            Error::InvalidSocket(_) => 12,
+            Error::MissingSwitch => 13,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    /// Formats the `Error` for display to the user.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Config(details) => write!(f, "Invalid configuration: {details}"),
+            Error::Connection(error) => write!(f, "Can't connect to database: {error}"),
+            Error::Authentication(details) => write!(f, "Access denied: {details}"),
+            Error::Tls(details) => write!(
+                f,
+                "TLS handshake failed: {details} (check ssl_mode and ssl_ca)"
+            ),
+            Error::Timeout => write!(f, "Connection to database timed out"),
+            Error::NotFound { query, suggestions } => {
+                write!(f, "Nothing found for \"{query}\"")?;
+                match suggestions.is_empty() {
+                    true => Ok(()),
+                    false => write!(f, ", did you mean {}?", suggestions.join(", ")),
+                }
+            }
+            Error::BrokenPath(details) => {
+                write!(f, "Wallsocket isn't patched to a switch: {details}")
+            }
+            Error::Ambiguous {
+                query,
+                count,
+                candidates,
+            } => {
+                write!(f, "\"{query}\" matches {count} entries")?;
+                for candidate in candidates {
+                    let (device, port) = match candidate.path.first() {
+                        Some(hop) => (hop.device_label.as_str(), hop.port_label.as_str()),
+                        None => ("?", "?"),
+                    };
+                    write!(
+                        f,
+                        "\n  {device} port {port} -> {} {}",
+                        candidate.switch_label, candidate.switch_port
+                    )?;
+                }
+                Ok(())
+            }
+            Error::Schema(details) => write!(f, "Unexpected database layout: {details}"),
+            Error::Database(error) => write!(f, "Database error: {error}"),
+            Error::InvalidSocket(input) => {
+                write!(f, "\"{input}\" is not a valid wallsocket description")
+            }
+            Error::MissingSwitch => write!(f, "No switch given"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    /// Returns the underlying `sqlx::Error`, if any.
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Connection(error) | Error::Database(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    /// Sorts an `sqlx::Error` into the matching `Error` variant.
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::Configuration(details) => Error::Config(details.to_string()),
+            sqlx::Error::PoolTimedOut => Error::Timeout,
+            sqlx::Error::Tls(details) => Error::Tls(details.to_string()),
+            // rustls reports a failed handshake, e.g. an untrusted server
+            // certificate, as an I/O error wrapping its own.
+            sqlx::Error::Io(ref ioerror) if is_tls(ioerror) => Error::Tls(ioerror.to_string()),
+            sqlx::Error::Io(_)
+            | sqlx::Error::Protocol(_)
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => Error::Connection(error),
+            sqlx::Error::RowNotFound => Error::not_found(""),
+            sqlx::Error::TypeNotFound { .. }
+            | sqlx::Error::ColumnIndexOutOfBounds { .. }
+            | sqlx::Error::ColumnNotFound(_)
+            | sqlx::Error::ColumnDecode { .. }
+            | sqlx::Error::Decode(_) => Error::Schema(error.to_string()),
+            sqlx::Error::Database(ref dberror) => {
+                match dberror.try_downcast_ref::<MySqlDatabaseError>() {
+                    // ER_DBACCESS_DENIED_ERROR, ER_ACCESS_DENIED_ERROR
+                    Some(mysqlerror) if matches!(mysqlerror.number(), 1044 | 1045) => {
+                        Error::Authentication(mysqlerror.message().to_owned())
+                    }
+                    // ER_BAD_DB_ERROR
+                    Some(mysqlerror) if mysqlerror.number() == 1049 => {
+                        Error::Config(mysqlerror.message().to_owned())
+                    }
+                    // ER_BAD_FIELD_ERROR, ER_NO_SUCH_TABLE
+                    Some(mysqlerror) if matches!(mysqlerror.number(), 1054 | 1146) => {
+                        Error::Schema(mysqlerror.message().to_owned())
+                    }
+                    _ => Error::Database(error),
+                }
+            }
+            _ => Error::Database(error),
+        }
+    }
+}
+
+/// Whether an I/O error was raised by rustls during the TLS handshake.
+fn is_tls(error: &std::io::Error) -> bool {
+    error
+        .get_ref()
+        .map_or(false, |inner| inner.is::<rustls::Error>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn rustls_handshake_failure_is_tls() {
+        let rustls = rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer);
+        let error = Error::from(sqlx::Error::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            rustls,
+        )));
+        assert!(matches!(error, Error::Tls(_)), "{error:?}");
+        assert_eq!(error.exit_code(), 10);
+    }
+
+    #[test]
+    fn other_io_errors_are_connection_errors() {
+        let error = Error::from(sqlx::Error::Io(io::ErrorKind::ConnectionRefused.into()));
+        assert!(matches!(error, Error::Connection(_)), "{error:?}");
+    }
+}
@@ -1,274 +1,159 @@
-//! This library is a collection of functions to interact with a MySQL
-//! used for openDCIM.
+//! This library is a collection of functions to interact with openDCIM.
 //!
-//! The `ccntool_core` crate provides a few functions to interact with a
-//! MySQL database that is being used by an instance of the
-//! openDCIM data center infrastructure management tool.
+//! The `ccntool_core` crate looks up the switch port a wallsocket is patched
+//! to, and everything around it, in the data of an instance of the openDCIM
+//! data center infrastructure management tool.
+//!
+//! All queries go through the [`DcimBackend`] trait, see the [`backend`]
+//! module, so frontends work with any source of openDCIM data:
+//! [`MySqlBackend`] reads openDCIM's MySQL database, [`MemoryBackend`]
+//! answers from lookups held in memory. The trait is `async` and expects
+//! to be run inside a tokio runtime. Frontends without a runtime of their
+//! own can use the synchronous adapter from the [`blocking`] module instead.
 //!
 //! # Example usage
 //!
-//! ```rust
+//! ```rust,no_run
 //! use ccntool_core::*;
 //!
-//! fn get_all_ports() {
-//!     let pool = connectdb().expect("Can't connect to database");
-//!     let all_ports = queryall(pool);
+//! async fn get_all_ports() -> Result<(), Error> {
+//!     let backend = backend::connect(Config::load()?).await?;
+//!     let all_ports = backend.list().await?;
 //!
 //!     println!("All ports: {:?}", all_ports);
+//!     Ok(())
 //! }
 //! ```
 
-use sqlx::{
-    mysql::{MySqlPoolOptions, MySqlRow},
-    MySql, Pool, Row,
-};
-use std::time::Duration;
-
-/// Establishes a connection to a MySQL database using provided credentials
-/// and base URL, either passed along the function or via dotenvy.
-///
-/// # Arguments
-///
-/// * `un` - An optional `String` representing the username to use for the
-/// database connection. If not provided, the function will attempt to
-/// retrieve the username from the `DCIMUSER` environment variable.
-/// * `pw` - An optional `String` representing the password to use for the
-/// database connection. If not provided, the function will attempt to
-/// retrieve the password from the `DCIMPASSWORD` environment variable.
-/// * `burl` - An optional `String` representing the base URL of the database
-/// server. If not provided, the function will attempt to retrieve the
-/// base URL from the `DCIMHOST` environment variable.
-///
-/// # Returns
-///
-/// Returns a `Result` containing a `Pool<MySql>` if the connection was
-/// successful, or an `sqlx::Error` if an error occurred.
-///
-/// # Examples
-///
-/// ```
-/// use ccntool_core::*;
-///
-/// #[tokio::main]
-/// async fn main() {
-///     // Username, Password and Hostname are passed via dotenvy
-///     let conn = match connectdb(None, None, None) {
-///         Ok(pool) => pool,
-///         Err(error) => {
-///             let error_message = format!("Connection timeout: {error}");
-///             self.error = error_message;
-///             eprintln!("Error: {}", error);
-///             return;
-///         }
-///     };
-///     ..
-/// }
-/// ```
-#[tokio::main(flavor = "current_thread")]
-pub async fn connectdb(
-    un: Option<String>,
-    pw: Option<String>,
-    burl: Option<String>,
-) -> Result<Pool<MySql>, sqlx::Error> {
-    /*
-    TODO:
-    - LDAP: get username from environemnt, then have use type in their password?
-    https://jstaf.github.io/posts/mariadb-ldap/
-    - TLS: enable crate to use openssl or gnutls? Probably openssl..?
-    */
-
-    let username: String = match un {
-        Some(un) => un,
-        _ => {
-            dotenvy::dotenv().ok();
-            dotenvy::var("DCIMUSER")
-                .expect("No Username via env")
-                .to_owned()
-        }
-    };
-
-    let password: String = match pw {
-        Some(pw) => pw,
-        _ => {
-            dotenvy::dotenv().ok();
-            dotenvy::var("DCIMPASSWORD")
-                .expect("No Password via env")
-                .to_owned()
-        }
-    };
-
-    let baseurl: String = match burl {
-        Some(burl) => burl,
-        _ => {
-            dotenvy::dotenv().ok();
-            dotenvy::var("DCIMHOST")
-                .expect("No BASEURL via env")
-                .to_owned()
-        }
-    };
-
-    let url: String = format!("mysql://{}:{}@{}:3306/dcim", username, password, baseurl);
-
-    let pool = MySqlPoolOptions::new()
-        .max_connections(5)
-        .acquire_timeout(Duration::from_secs(5))
-        .connect(&url)
-        .await?;
-    Ok(pool)
+pub mod backend;
+pub use backend::DcimBackend;
+
+pub mod blocking;
+
+pub mod config;
+pub use config::Config;
+
+mod credentials;
+
+mod device;
+pub use device::{Contact, DeviceDetails};
+
+mod error;
+pub use error::Error;
+
+mod memory;
+pub use memory::MemoryBackend;
+
+mod mysql;
+pub use mysql::MySqlBackend;
+
+pub mod naming;
+use naming::IdColumn;
+pub use naming::{Identifier, NamingScheme, PortKind};
+
+mod search;
+pub use search::{SearchEntry, SearchIndex, SearchKind};
+
+mod socket;
+pub use socket::{RoomId, SocketId};
+
+mod suggest;
+
+mod trace;
+pub use trace::{Chassis, Hop, Location, MAX_HOPS};
+
+mod uplink;
+pub use uplink::{Uplink, MAX_UPLINK_LEVELS};
+
+use std::net::IpAddr;
+
+/// Result of a wallsocket lookup, describing the switch port a wallsocket
+/// is patched to and every patch panel port in between.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct PortLookup {
+    /// Switch hostname (`Label` field from `fac_Device` table)
+    pub switch_label: String,
+    /// Switch IP address (`PrimaryIP` field from `fac_Device` table), the
+    /// one of the stack master or chassis if a member has none of its own,
+    /// `None` if it isn't set or invalid
+    pub switch_ip: Option<IpAddr>,
+    /// Switch port (`Label` field from `fac_Ports` table)
+    pub switch_port: String,
+    /// Port description of the switch port (`Notes` field from `fac_Ports`
+    /// table)
+    pub port_notes: String,
+    /// Switch device ID (`DeviceID` field from `fac_Device` table)
+    pub switch_device_id: i32,
+    /// Device ID of the patch panel the wallsocket is located on
+    pub patchpanel_device_id: i32,
+    /// Port number of the wallsocket on the patch panel
+    pub patchpanel_port_number: i32,
+    /// Every port passed from the wallsocket to the switch, see
+    /// [`DcimBackend::trace`]
+    #[serde(default)]
+    pub path: Vec<Hop>,
+    /// Kind of the wallsocket, `None` if it matches no naming scheme
+    #[serde(default)]
+    pub kind: Option<PortKind>,
+    /// Column of the first port on the path holding the wallsocket
+    /// description, as configured by the naming schemes
+    #[serde(default)]
+    pub id_column: Option<IdColumn>,
+    /// Hardware, template and ownership details of the switch, `None` if
+    /// the database doesn't provide them
+    #[serde(default)]
+    pub switch_details: Option<DeviceDetails>,
 }
 
-/// This function will query all available, valid wallsocket descriptions.
-///
-/// # Arguments
-///
-/// * `conn` - A `Pool<MySql>` representing a connection to the
-/// `dcim` database.
-///
-/// # Returns
-///
-/// Returns a `Result` containing a `Vec<String>` if the connection was
-/// successful, or an `sqlx::Error` if an error occurred.
-///
-/// # Errors
-///
-/// Returns an error of type `sqlx::Error` if no matching row is found in
-/// the `fac_Ports` table.
-///
-/// # Example
-///
-/// ```rust
-/// fn main() {
-///     let ports: Vec<String> = vec![];
-///     let all_ports = queryall(connectdb(None, None, None)
-///         .expect("Can't connect to database!"))
-///         .unwrap();
-///
-///     println!("All ports: {:?}", all_ports);
-/// }
-/// ```
-
-#[tokio::main(flavor = "current_thread")]
-pub async fn queryall(conn: Pool<MySql>) -> Result<Vec<String>, sqlx::Error> {
-    let mut allports: Vec<String> = Vec::new();
-
-    // TODO/CHECK: is this regex really fetching _all_ valid Notes?
-    let allvalidnotes = sqlx::query(
-        r#"
-SELECT Notes
-FROM fac_Ports
-WHERE Notes REGEXP '^[0-9]+.[EU0-9]+.[0-9]+-[0-9a-z/,]+?$'
-OR Notes REGEXP '^MT-|.*APD.*|.*APP.*|.*APR.*|.*APM.*|.*APK.*'
-        "#,
-    )
-    .fetch_all(&mut *conn.acquire().await?)
-    .await?;
-
-    for row in allvalidnotes {
-        let notes: String = row.get("Notes");
-        allports.push(notes);
+impl PortLookup {
+    /// Returns the wallsocket description (the [`Self::id_column`] field of
+    /// the first port on the path, or if it isn't known, the `Notes` field
+    /// or the `Label` field if there are no notes).
+    pub fn wallsocket(&self) -> &str {
+        self.path.first().map_or("", |hop| match self.id_column {
+            Some(column) => hop.identifier(column),
+            None if hop.port_notes.is_empty() => &hop.port_label,
+            None => &hop.port_notes,
+        })
     }
 
-    Ok(allports)
-}
+    /// Returns the switch IP address for display, `unknown` if it isn't set
+    /// or invalid.
+    pub fn switch_ip_text(&self) -> String {
+        self.switch_ip
+            .map_or_else(|| "unknown".to_owned(), |ip| ip.to_string())
+    }
 
-/// Executes a SQL query against the `dcim` database and returns a vector
-/// of strings containing the results. The query looks for the first port
-/// in the `fac_Ports` table with a matching `Notes` field to the `notes`
-/// argument, and returns various details about the associated switch and
-/// port. If no matching row is found, an error is returned.
-///
-/// # Arguments
-///
-/// * `conn` - A `Pool<MySql>` representing a connection to the
-/// `dcim` database.
-/// * `notes` - A `&str` containing the `Notes` field value to match against
-/// in the `fac_Ports` table.
-///
-/// # Returns
-///
-/// A `Result` containing a vector of strings, where each string
-/// represents a different detail about the switch and port.
-/// The vector contains the following fields, in order:
-///
-/// * `results[0]` - Switch hostname (`Label` field from `fac_Device` table)
-/// * `results[1]` - Port description (`Notes` field from `fac_Ports` table)
-/// * `results[2]` - Switch port (`Label` field from `fac_Ports` table)
-/// * `results[3]` - Switch IP address (`PrimaryIP` field from `fac_Device`
-/// table)
-/// * `results[4]` - Switch device ID (`DeviceID` field from `fac_Device`
-/// table)
-///
-/// # Errors
-///
-/// Returns an error of type `sqlx::Error` if no matching row is found in
-/// the `fac_Ports` table.
-///
-/// # Examples
-///
-/// ```rust
-/// fn main() {
-///     let results = match myquery(connectdb(None, None, None), "01.1.001-1") {
-///         Ok(rows) => {
-///            self.error.clear();
-///            rows
-///         }
-///         Err(error) => {
-///            let error_message = format!("Received garbage: {error}");
-///            self.error = error_message;
-///            eprintln!("Error: {error}");
-///            return;
-///         }
-///     };
-///     ..
-/// }
-/// ```
-///
-#[tokio::main(flavor = "current_thread")]
-pub async fn myquery(conn: Pool<MySql>, notes: &str) -> Result<Vec<String>, sqlx::Error> {
-    let mut results: Vec<String> = Vec::new();
+    /// Returns where the switch is mounted, or `None` if it isn't assigned
+    /// to a cabinet.
+    pub fn switch_location(&self) -> Option<&Location> {
+        self.path.last().and_then(|hop| hop.location.as_ref())
+    }
 
-    let selectedrows = sqlx::query(
-        r#"
-SELECT p1.PortNumber AS '@PortNumber', p1.DeviceID AS '@DeviceID',
-  p2.ConnectedDeviceID AS '@ConnectedDeviceID',
-  p2.ConnectedPort AS '@ConnectedPort', d1.DeviceID AS '@SwitchDeviceID',
-  d1.Label AS '@SwitchLabel', p3.Notes AS '@PortNotes',
-  p3.Label AS '@SwitchPort', d1.PrimaryIP AS '@SwitchIP'
-FROM fac_Ports p1
-  JOIN fac_Ports p2 ON p1.DeviceID = p2.DeviceID AND p1.PortNumber = -p2.PortNumber
-  JOIN fac_Device d1 ON d1.DeviceID = p2.ConnectedDeviceID
-  JOIN fac_Ports p3 ON p3.DeviceID = p2.ConnectedDeviceID AND p3.PortNumber = p2.ConnectedPort
-WHERE p1.Notes = ? LIMIT 1
-        "#,
-    )
-    .bind(notes.trim())
-    .map(|row: MySqlRow| {
-        (
-            row.get::<i32, _>("@PortNumber"),
-            row.get::<i32, _>("@DeviceID"),
-            row.get::<i32, _>("@ConnectedDeviceID"),
-            row.get::<i32, _>("@ConnectedPort"),
-            row.get::<i32, _>("@SwitchDeviceID"),
-            row.get::<String, _>("@SwitchLabel"),
-            row.get::<String, _>("@PortNotes"),
-            row.get::<String, _>("@SwitchPort"),
-            row.get::<String, _>("@SwitchIP"),
-        )
-    })
-    .fetch_all(&mut *conn.acquire().await?)
-    .await?;
+    /// Returns the chassis or stack the switch is a member of, including its
+    /// slot number, or `None` for standalone switches.
+    pub fn switch_chassis(&self) -> Option<&Chassis> {
+        self.path.last().and_then(|hop| hop.chassis.as_ref())
+    }
 
-    for i in selectedrows {
-        results.push(i.5); // results[0] -> Switch Hostname
-        results.push(i.6); // results[1] -> Port Description
-        results.push(i.7); // results[2] -> Switch Port
-        results.push(i.8); // results[3] -> Switch IP
-        results.push(i.4.to_string()); // results[4] -> Switch DeviceID
+    /// Describes the patch cable plugged into the switch port, like
+    /// `blue Cat6A`, see [`Hop::cable`].
+    pub fn switch_cable(&self) -> Option<String> {
+        self.path.last().and_then(Hop::cable)
     }
 
-    if results.is_empty() {
-        return Err(sqlx::Error::RowNotFound);
+    /// Returns the patch panel ports passed on the way to the switch, front
+    /// and rear port of each, starting with the wallsocket.
+    pub fn patchpanels(&self) -> &[Hop] {
+        match self.path.split_last() {
+            Some((_, patchpanels)) => patchpanels,
+            None => &[],
+        }
     }
 
-    Ok(results)
+    /// Returns the wallsocket description parsed as a [`SocketId`], or `None`
+    /// if it doesn't follow the `01.1.001-1` scheme, e.g. for access points.
+    pub fn socket_id(&self) -> Option<SocketId> {
+        self.wallsocket().parse().ok()
+    }
 }
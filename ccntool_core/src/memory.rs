@@ -0,0 +1,572 @@
+//! A [`DcimBackend`] answering from lookups held in memory, without any
+//! database.
+
+use crate::{
+    backend::BoxFuture, naming, search, suggest, Config, DcimBackend, Error, Hop, Identifier,
+    PortLookup, RoomId, SearchIndex, SocketId, Uplink, MAX_UPLINK_LEVELS,
+};
+use std::{collections::BTreeMap, net::IpAddr};
+
+/// A backend answering from [`PortLookup`]s and [`Uplink`]s held in memory,
+/// e.g. an offline snapshot of what another backend returned or fixtures
+/// for tests.
+///
+/// Every lookup stands for one wallsocket, the first [`Hop`] of its path
+/// being the wallsocket's patch panel port. The uplinks are taken as they
+/// are and followed all the way up, as the configured core pattern is a
+/// MySQL regular expression.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use ccntool_core::*;
+///
+/// # async fn run() -> Result<(), Error> {
+/// let online = backend::connect(Config::load()?).await?;
+/// let offline = MemoryBackend::new(online.room_sockets("01.1.001").await?);
+/// let lookup = offline.lookup("1.1.1-1").await?;
+///
+/// println!("{} {}", lookup.switch_label, lookup.switch_port);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryBackend {
+    config: Config,
+    lookups: Vec<PortLookup>,
+    uplinks: Vec<Uplink>,
+}
+
+impl MemoryBackend {
+    /// Creates a new `MemoryBackend` knowing the wallsockets of `lookups`,
+    /// using the default configuration.
+    pub fn new(lookups: Vec<PortLookup>) -> Self {
+        MemoryBackend {
+            lookups,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the configuration, e.g. for the naming schemes or the openDCIM
+    /// web URL. Lookups not telling which column holds their wallsocket
+    /// description take it from the naming schemes.
+    pub fn with_config(mut self, config: Config) -> Self {
+        let columns = naming::columns(&config.naming);
+        for lookup in &mut self.lookups {
+            if lookup.id_column.is_none() {
+                lookup.id_column = lookup.path.first().and_then(|hop| hop.id_column(&columns));
+            }
+        }
+        self.config = config;
+        self
+    }
+
+    /// Sets the uplinks between the switches.
+    pub fn with_uplinks(mut self, uplinks: Vec<Uplink>) -> Self {
+        self.uplinks = uplinks;
+        self
+    }
+
+    /// Looks up a single wallsocket, see [`DcimBackend::lookup`].
+    fn find(&self, name: &str) -> Result<PortLookup, Error> {
+        let name = name.trim();
+        let columns = naming::columns(&self.config.naming);
+        let socket: Option<SocketId> = name.parse().ok();
+        let canonical = socket.as_ref().map(SocketId::to_string);
+        let starting = |matches: &dyn Fn(&Hop) -> bool| -> Vec<&PortLookup> {
+            self.lookups
+                .iter()
+                .filter(|lookup| lookup.path.first().map_or(false, matches))
+                .collect()
+        };
+
+        // Try the name as given first, then in its canonical form, then
+        // among the ports shared by several outlets.
+        let mut found = match name.is_empty() {
+            true => Vec::new(),
+            false => starting(&|hop| hop.is_named(name, &columns)),
+        };
+        if let (true, Some(canonical)) = (found.is_empty(), &canonical) {
+            found = starting(&|hop| hop.is_named(canonical, &columns));
+        }
+        if let (true, Some(socket)) = (found.is_empty(), &socket) {
+            found = starting(&|hop| hop.covers(socket));
+        }
+
+        match found.as_slice() {
+            [] => {
+                let identifiers = self.identifiers_now();
+                let query = canonical.as_deref().unwrap_or(name);
+                let candidates = identifiers.iter().map(|id| id.name.as_str());
+                Err(Error::NotFound {
+                    query: name.to_owned(),
+                    suggestions: suggest::closest(query, candidates),
+                })
+            }
+            [lookup] => Ok((*lookup).clone()),
+            _ => Err(Error::Ambiguous {
+                query: name.to_owned(),
+                count: found.len(),
+                candidates: found.into_iter().cloned().collect(),
+            }),
+        }
+    }
+
+    /// Every switch port known, from the lookups and the uplinks, with the
+    /// IP address of its switch if known.
+    fn switches(&self) -> impl Iterator<Item = (&Hop, Option<IpAddr>)> {
+        let lookups = self
+            .lookups
+            .iter()
+            .filter_map(|lookup| Some((lookup.path.last()?, lookup.switch_ip)));
+        let uplinks = self.uplinks.iter().flat_map(|uplink| {
+            [
+                (&uplink.port, None),
+                (&uplink.switch_port, uplink.switch_ip),
+            ]
+        });
+
+        lookups.chain(uplinks)
+    }
+
+    /// Finds the `DeviceID` of the switch with the given label, IP address
+    /// or `DeviceID`.
+    fn find_switch(&self, switch: &str) -> Result<i32, Error> {
+        let switch = switch.trim();
+        if switch.is_empty() {
+            return Err(Error::MissingSwitch);
+        }
+
+        let mut ids: Vec<i32> = self
+            .switches()
+            .filter_map(|(hop, ip)| switch_id(hop, ip, switch))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        match ids.as_slice() {
+            [] => Err(Error::not_found(switch)),
+            [id] => Ok(*id),
+            _ => Err(Error::Ambiguous {
+                query: switch.to_owned(),
+                count: ids.len(),
+                candidates: Vec::new(),
+            }),
+        }
+    }
+
+    /// Lists the wallsockets patched to the switch with `DeviceID`
+    /// `switch` or its stack members, see [`DcimBackend::switch_sockets`].
+    fn sockets_of(&self, switch: i32) -> Vec<PortLookup> {
+        let mut lookups: Vec<(&Hop, &PortLookup)> = self
+            .lookups
+            .iter()
+            .filter_map(|lookup| Some((lookup.path.last()?, lookup)))
+            .filter(|(hop, _)| on_switch(hop, switch))
+            .collect();
+        lookups.sort_by_key(|(hop, _)| {
+            let slot = hop.chassis.as_ref().map_or(0, |chassis| chassis.slot);
+            (hop.device_id != switch, slot, hop.port_number)
+        });
+
+        lookups
+            .into_iter()
+            .map(|(_, lookup)| lookup.clone())
+            .collect()
+    }
+
+    /// Lists every wallsocket in a room, see [`DcimBackend::room_sockets`].
+    fn sockets_in(&self, room: &str) -> Result<Vec<PortLookup>, Error> {
+        let room: RoomId = room.parse()?;
+        let mut lookups: Vec<(SocketId, &PortLookup)> = self
+            .lookups
+            .iter()
+            .filter_map(|lookup| {
+                let hop = lookup.path.first()?;
+                let socket = [&hop.port_notes, &hop.port_label]
+                    .into_iter()
+                    .find_map(|name| name.parse::<SocketId>().ok())?;
+                (socket.room_id() == &room).then_some((socket, lookup))
+            })
+            .collect();
+        if lookups.is_empty() {
+            return Err(Error::not_found(room.to_string()));
+        }
+        lookups.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(lookups
+            .into_iter()
+            .map(|(_, lookup)| lookup.clone())
+            .collect())
+    }
+
+    /// Follows the uplinks of a switch level by level, see
+    /// [`DcimBackend::uplinks`].
+    fn uplinks_of(&self, switch: &str) -> Result<Vec<Uplink>, Error> {
+        let switch = self.find_switch(switch)?;
+        // Stacks and chassis count as one switch.
+        let unit = self
+            .switches()
+            .find_map(|(hop, _)| (hop.device_id == switch).then(|| hop.unit_id()))
+            .unwrap_or(switch);
+
+        let mut chain = Vec::new();
+        let mut reached = vec![unit];
+        let mut frontier = vec![unit];
+        for _ in 0..MAX_UPLINK_LEVELS {
+            let links: Vec<&Uplink> = self
+                .uplinks
+                .iter()
+                .filter(|link| frontier.contains(&link.port.unit_id()))
+                .filter(|link| !reached.contains(&link.switch_port.unit_id()))
+                .collect();
+            if links.is_empty() {
+                break;
+            }
+
+            frontier = links
+                .iter()
+                .map(|link| link.switch_port.unit_id())
+                .collect();
+            frontier.sort_unstable();
+            frontier.dedup();
+            reached.extend(&frontier);
+            chain.extend(links.into_iter().cloned());
+        }
+
+        Ok(chain)
+    }
+
+    /// Lists the identifiers of all wallsockets with a kind, see
+    /// [`DcimBackend::identifiers`].
+    fn identifiers_now(&self) -> Vec<Identifier> {
+        naming::collect(
+            self.lookups
+                .iter()
+                .filter_map(|lookup| Some((lookup.wallsocket().to_owned(), lookup.kind?))),
+        )
+    }
+
+    /// Builds the search index, see [`DcimBackend::search_index`].
+    fn index(&self) -> SearchIndex {
+        // Ordered by hostname, keeping the first IP address known.
+        let mut switches: BTreeMap<String, String> = BTreeMap::new();
+        for (hop, ip) in self.switches() {
+            let known = switches.entry(hop.device_label.clone()).or_default();
+            if let (true, Some(ip)) = (known.is_empty(), ip) {
+                *known = ip.to_string();
+            }
+        }
+
+        search::build(self.identifiers_now(), switches.into_iter().collect())
+    }
+}
+
+/// Returns the `DeviceID` that `switch` names, if it is the hostname, IP
+/// address or `DeviceID` of the chassis the port `hop` is on, or else of
+/// its own device.
+fn switch_id(hop: &Hop, ip: Option<IpAddr>, switch: &str) -> Option<i32> {
+    let names = |label: &str, id: i32, ip: Option<IpAddr>| {
+        label.eq_ignore_ascii_case(switch)
+            || id.to_string() == switch
+            || ip.map_or(false, |ip| ip.to_string() == switch)
+    };
+    if let Some(chassis) = &hop.chassis {
+        if names(&chassis.label, chassis.device_id, chassis.primary_ip) {
+            return Some(chassis.device_id);
+        }
+    }
+
+    names(&hop.device_label, hop.device_id, ip).then_some(hop.device_id)
+}
+
+/// Whether the port `hop` is on the switch with `DeviceID` `switch` or one
+/// of its stack members.
+fn on_switch(hop: &Hop, switch: i32) -> bool {
+    hop.device_id == switch
+        || hop
+            .chassis
+            .as_ref()
+            .map_or(false, |chassis| chassis.device_id == switch)
+}
+
+impl DcimBackend for MemoryBackend {
+    fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<PortLookup, Error>> {
+        Box::pin(async move { self.find(name) })
+    }
+
+    fn reverse_lookup<'a>(
+        &'a self,
+        switch: &'a str,
+        port: &'a str,
+    ) -> BoxFuture<'a, Result<PortLookup, Error>> {
+        Box::pin(async move {
+            let port = port.trim();
+            let device_id = self.find_switch(switch)?;
+            let mut found: Vec<PortLookup> = self
+                .sockets_of(device_id)
+                .into_iter()
+                .filter(|lookup| lookup.switch_port.eq_ignore_ascii_case(port.trim()))
+                .collect();
+            let query = format!("{} {}", switch.trim(), port.trim());
+            match found.len() {
+                0 => Err(Error::not_found(query)),
+                1 => Ok(found.remove(0)),
+                count => Err(Error::Ambiguous {
+                    query,
+                    count,
+                    candidates: Vec::new(),
+                }),
+            }
+        })
+    }
+
+    fn switch_sockets<'a>(
+        &'a self,
+        switch: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PortLookup>, Error>> {
+        Box::pin(async move { Ok(self.sockets_of(self.find_switch(switch)?)) })
+    }
+
+    fn room_sockets<'a>(&'a self, room: &'a str) -> BoxFuture<'a, Result<Vec<PortLookup>, Error>> {
+        Box::pin(async move { self.sockets_in(room) })
+    }
+
+    fn uplinks<'a>(&'a self, switch: &'a str) -> BoxFuture<'a, Result<Vec<Uplink>, Error>> {
+        Box::pin(async move { self.uplinks_of(switch) })
+    }
+
+    fn identifiers(&self) -> BoxFuture<'_, Result<Vec<Identifier>, Error>> {
+        Box::pin(async move { Ok(self.identifiers_now()) })
+    }
+
+    fn search_index(&self) -> BoxFuture<'_, Result<SearchIndex, Error>> {
+        Box::pin(async move { Ok(self.index()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blocking::DcimClient, naming::IdColumn, Chassis, NamingScheme, PortKind, SearchKind,
+    };
+
+    fn hop(device_id: i32, device_label: &str, port_number: i32, port_label: &str) -> Hop {
+        Hop {
+            device_id,
+            device_label: device_label.to_owned(),
+            port_number,
+            port_label: port_label.to_owned(),
+            port_notes: String::new(),
+            location: None,
+            media_type: None,
+            color: None,
+            chassis: None,
+        }
+    }
+
+    /// A wallsocket on patch panel port `number`, patched to `switch_port`.
+    fn wallsocket(notes: &str, number: i32, switch_port: Hop) -> PortLookup {
+        let mut front = hop(100, "PP-E2-03", number, &number.to_string());
+        front.port_notes = notes.to_owned();
+        let rear = hop(100, "PP-E2-03", -number, &number.to_string());
+
+        PortLookup {
+            switch_label: switch_port.device_label.clone(),
+            switch_ip: "10.1.2.5".parse().ok(),
+            switch_port: switch_port.port_label.clone(),
+            port_notes: switch_port.port_notes.clone(),
+            switch_device_id: switch_port.device_id,
+            patchpanel_device_id: front.device_id,
+            patchpanel_port_number: front.port_number,
+            path: vec![front, rear, switch_port],
+            kind: Some(PortKind::Wallsocket),
+            id_column: None,
+            switch_details: None,
+        }
+    }
+
+    fn member(slot: i32, port_number: i32) -> Hop {
+        let mut member = hop(10 + slot, "sw-h1-e2-m", port_number, "");
+        member.port_label = format!("Gi{slot}/0/{port_number}");
+        member.chassis = Some(Chassis {
+            device_id: 1,
+            label: "sw-h1-e2".to_owned(),
+            primary_ip: "10.1.2.5".parse().ok(),
+            slot,
+        });
+        member
+    }
+
+    fn client() -> DcimClient {
+        let uplinks = vec![
+            Uplink {
+                port: member(1, 49),
+                path: Vec::new(),
+                switch_port: hop(2, "dsw-h1", 5, "Te1/0/5"),
+                switch_ip: None,
+            },
+            Uplink {
+                port: hop(2, "dsw-h1", 1, "Te1/0/1"),
+                path: Vec::new(),
+                switch_port: hop(3, "core-1", 7, "Te1/0/7"),
+                switch_ip: "10.0.0.1".parse().ok(),
+            },
+        ];
+        let backend = MemoryBackend::new(vec![
+            wallsocket("01.1.002-1", 3, member(2, 1)),
+            wallsocket("01.1.001-1", 1, member(1, 17)),
+            wallsocket("01.1.001-2,3", 2, member(1, 18)),
+            wallsocket("MT-1", 4, member(1, 20)),
+            wallsocket("MT-1", 5, member(1, 21)),
+        ])
+        .with_uplinks(uplinks);
+
+        DcimClient::new(Box::new(backend)).unwrap()
+    }
+
+    fn wallsockets(lookups: &[PortLookup]) -> Vec<&str> {
+        lookups.iter().map(PortLookup::wallsocket).collect()
+    }
+
+    #[test]
+    fn lookup_tries_canonical_and_shared_ports() {
+        let client = client();
+        assert_eq!(client.lookup("01.1.001-1").unwrap().switch_port, "Gi1/0/17");
+        assert_eq!(client.lookup(" 1.1.1-1 ").unwrap().switch_port, "Gi1/0/17");
+        assert_eq!(client.lookup("01.1.001-3").unwrap().switch_port, "Gi1/0/18");
+        assert_eq!(client.trace("01.1.001-1").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn lookup_reports_suggestions_and_conflicts() {
+        let client = client();
+        match client.lookup("01.1.001-4") {
+            Err(Error::NotFound { suggestions, .. }) => {
+                assert!(suggestions.contains(&"01.1.001-1".to_owned()))
+            }
+            other => panic!("expected NotFound, got {other:?}"),
+        }
+        match client.lookup("MT-1") {
+            Err(Error::Ambiguous {
+                count, candidates, ..
+            }) => {
+                assert_eq!((count, candidates.len()), (2, 2))
+            }
+            other => panic!("expected Ambiguous, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn lookup_many_keeps_the_order() {
+        let results = client()
+            .lookup_many(&["01.1.002-1", "nope", "01.1.001-1"])
+            .unwrap();
+        assert_eq!(results[0].as_ref().unwrap().switch_port, "Gi2/0/1");
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().switch_port, "Gi1/0/17");
+    }
+
+    #[test]
+    fn switch_is_found_by_label_ip_or_id() {
+        let client = client();
+        for switch in ["sw-h1-e2", "10.1.2.5", "1"] {
+            let lookup = client.reverse_lookup(switch, "gi1/0/17").unwrap();
+            assert_eq!(lookup.wallsocket(), "01.1.001-1");
+        }
+        assert!(matches!(
+            client.reverse_lookup("sw-h1-e2", "Gi1/0/1"),
+            Err(Error::NotFound { .. })
+        ));
+        assert!(matches!(
+            client.switch_sockets("sw-x"),
+            Err(Error::NotFound { .. })
+        ));
+        assert!(matches!(
+            client.switch_sockets(" "),
+            Err(Error::MissingSwitch)
+        ));
+    }
+
+    #[test]
+    fn switch_sockets_are_ordered_by_slot_and_port() {
+        let lookups = client().switch_sockets("sw-h1-e2").unwrap();
+        assert_eq!(
+            wallsockets(&lookups),
+            ["01.1.001-1", "01.1.001-2,3", "MT-1", "MT-1", "01.1.002-1"]
+        );
+    }
+
+    #[test]
+    fn room_sockets_are_ordered_by_socket() {
+        let client = client();
+        let lookups = client.room_sockets("1.1.1").unwrap();
+        assert_eq!(wallsockets(&lookups), ["01.1.001-1", "01.1.001-2,3"]);
+        assert!(matches!(
+            client.room_sockets("01.1.009"),
+            Err(Error::NotFound { .. })
+        ));
+        assert!(matches!(
+            client.room_sockets("nope"),
+            Err(Error::InvalidSocket(_))
+        ));
+    }
+
+    #[test]
+    fn uplinks_are_followed_to_the_top() {
+        let uplinks = client().uplinks("sw-h1-e2").unwrap();
+        let upper: Vec<&str> = uplinks
+            .iter()
+            .map(|uplink| uplink.switch_port.device_label.as_str())
+            .collect();
+        assert_eq!(upper, ["dsw-h1", "core-1"]);
+    }
+
+    #[test]
+    fn identifiers_and_search_cover_every_wallsocket() {
+        let client = client();
+        assert_eq!(
+            client.list().unwrap(),
+            [
+                "01.1.001-1",
+                "01.1.001-2",
+                "01.1.001-3",
+                "01.1.002-1",
+                "MT-1"
+            ]
+        );
+        let index = client.search_index().unwrap();
+        let switch = index
+            .entries()
+            .iter()
+            .find(|entry| entry.kind == SearchKind::Switch && entry.text == "core-1")
+            .unwrap();
+        assert_eq!(switch.detail.as_deref(), Some("10.0.0.1"));
+        assert_eq!(client.search("10.0.0").unwrap()[0].text, "10.0.0.1");
+    }
+
+    #[test]
+    fn wallsockets_are_taken_from_the_configured_column() {
+        let mut lookup = wallsocket("see ticket 42", 7, member(1, 30));
+        lookup.path[0].port_label = "A01-001/1".to_owned();
+        let config = Config {
+            naming: vec![NamingScheme {
+                pattern: "^A[0-9]+-".to_owned(),
+                column: IdColumn::Label,
+                kind: PortKind::Wallsocket,
+            }],
+            ..Default::default()
+        };
+        let backend = MemoryBackend::new(vec![lookup]).with_config(config);
+        let client = DcimClient::new(Box::new(backend)).unwrap();
+
+        assert_eq!(client.list().unwrap(), ["A01-001/1"]);
+        let lookup = client.lookup("a01-001/1").unwrap();
+        assert_eq!(lookup.wallsocket(), "A01-001/1");
+    }
+}
@@ -0,0 +1,186 @@
+//! The [`DcimBackend`] querying openDCIM's MySQL database directly.
+//!
+//! All SQL of this crate lives in here. The queries read the ports and
+//! follow the paths of a whole batch with a handful of statements instead
+//! of several per wallsocket, and fall back gracefully on openDCIM versions
+//! laid out differently.
+
+mod device;
+mod lookup;
+mod naming;
+mod search;
+mod trace;
+mod uplink;
+
+use crate::{
+    backend::BoxFuture, Config, DcimBackend, Error, Identifier, PortLookup, SearchIndex, Uplink,
+    MAX_HOPS,
+};
+use sqlx::{mysql::MySqlPoolOptions, pool::PoolConnection, MySql, Pool};
+use std::{future::Future, sync::Mutex, time::Duration};
+
+/// A long-lived handle to the openDCIM database.
+///
+/// The backend is created once and keeps its connection pool around, so
+/// consecutive lookups don't pay for a new TCP and authentication handshake.
+/// If the pool goes stale, e.g. because the database server was restarted,
+/// the backend reconnects with the configuration it was created with and
+/// retries the query once.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use ccntool_core::*;
+///
+/// # async fn run() -> Result<(), Error> {
+/// let backend = MySqlBackend::connect(Config::load()?).await?;
+/// let lookup = backend.lookup("01.1.001-1").await?;
+///
+/// println!("{} {}", lookup.switch_label, lookup.switch_port);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MySqlBackend {
+    config: Config,
+    pool: Mutex<Pool<MySql>>,
+}
+
+impl MySqlBackend {
+    /// Creates a new `MySqlBackend` and connects it using `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if the host or a credential is missing, and
+    /// [`Error::Connection`], [`Error::Authentication`], [`Error::Tls`] or
+    /// [`Error::Timeout`] if the database can't be reached.
+    pub async fn connect(config: Config) -> Result<Self, Error> {
+        let pool = connectdb(&config).await?;
+
+        Ok(MySqlBackend {
+            config,
+            pool: Mutex::new(pool),
+        })
+    }
+
+    /// Returns the current pool, replacing it first if it has been closed.
+    async fn pool(&self) -> Result<Pool<MySql>, Error> {
+        let pool = self.pool.lock().unwrap().clone();
+        if pool.is_closed() {
+            return self.reconnect().await;
+        }
+
+        Ok(pool)
+    }
+
+    /// Replaces the pool with a freshly connected one.
+    async fn reconnect(&self) -> Result<Pool<MySql>, Error> {
+        let pool = connectdb(&self.config).await?;
+        let stale = std::mem::replace(&mut *self.pool.lock().unwrap(), pool.clone());
+        stale.close().await;
+
+        Ok(pool)
+    }
+
+    /// Runs `query` on a connection from the pool and, if the connection
+    /// turns out to be broken, runs it once more on a new pool.
+    async fn retry<T, F, Fut>(&self, query: F) -> Result<T, Error>
+    where
+        F: Fn(PoolConnection<MySql>) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let attempt = |pool: Pool<MySql>| {
+            let query = &query;
+            async move { query(pool.acquire().await?).await }
+        };
+        match attempt(self.pool().await?).await {
+            Err(Error::Connection(_)) => attempt(self.reconnect().await?).await,
+            result => result,
+        }
+    }
+}
+
+impl DcimBackend for MySqlBackend {
+    fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn lookup<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<PortLookup, Error>> {
+        Box::pin(async move { self.lookup_many(&[name]).await?.remove(0) })
+    }
+
+    fn lookup_many<'a>(
+        &'a self,
+        names: &'a [&'a str],
+    ) -> BoxFuture<'a, Result<Vec<Result<PortLookup, Error>>, Error>> {
+        let naming = &self.config.naming;
+        Box::pin(self.retry(move |mut conn| async move {
+            lookup::lookup_many(&mut conn, naming, names, MAX_HOPS).await
+        }))
+    }
+
+    fn reverse_lookup<'a>(
+        &'a self,
+        switch: &'a str,
+        port: &'a str,
+    ) -> BoxFuture<'a, Result<PortLookup, Error>> {
+        let naming = &self.config.naming;
+        Box::pin(self.retry(move |mut conn| async move {
+            lookup::reverse_lookup(&mut conn, naming, switch, port).await
+        }))
+    }
+
+    fn switch_sockets<'a>(
+        &'a self,
+        switch: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PortLookup>, Error>> {
+        let naming = &self.config.naming;
+        Box::pin(self.retry(move |mut conn| async move {
+            lookup::switch_sockets(&mut conn, naming, switch).await
+        }))
+    }
+
+    fn room_sockets<'a>(&'a self, room: &'a str) -> BoxFuture<'a, Result<Vec<PortLookup>, Error>> {
+        let naming = &self.config.naming;
+        Box::pin(self.retry(move |mut conn| async move {
+            lookup::room_sockets(&mut conn, naming, room).await
+        }))
+    }
+
+    fn uplinks<'a>(&'a self, switch: &'a str) -> BoxFuture<'a, Result<Vec<Uplink>, Error>> {
+        let core = self.config.core.as_deref();
+        Box::pin(
+            self.retry(
+                move |mut conn| async move { lookup::uplinks(&mut conn, switch, core).await },
+            ),
+        )
+    }
+
+    fn identifiers(&self) -> BoxFuture<'_, Result<Vec<Identifier>, Error>> {
+        let naming = &self.config.naming;
+        Box::pin(
+            self.retry(move |mut conn| async move { naming::identifiers(&mut conn, naming).await }),
+        )
+    }
+
+    fn search_index(&self) -> BoxFuture<'_, Result<SearchIndex, Error>> {
+        let naming = &self.config.naming;
+        Box::pin(self.retry(move |mut conn| async move { search::index(&mut conn, naming).await }))
+    }
+}
+
+/// Establishes a connection pool to the MySQL database described by
+/// `config`.
+async fn connectdb(config: &Config) -> Result<Pool<MySql>, Error> {
+    /*
+    TODO:
+    - LDAP: get username from environemnt, then have use type in their password?
+    https://jstaf.github.io/posts/mariadb-ldap/
+    */
+
+    let pool = MySqlPoolOptions::new()
+        .max_connections(5)
+        .acquire_timeout(Duration::from_secs(5))
+        .connect_with(config.connect_options()?)
+        .await?;
+    Ok(pool)
+}
@@ -0,0 +1,153 @@
+use crate::{Contact, DeviceDetails, Error};
+use sqlx::{mysql::MySqlRow, MySqlConnection, Row};
+use std::collections::HashMap;
+
+/// Tables the contacts may be stored in, in the order they are tried:
+/// openDCIM 4.3 replaced `fac_Contact` with `fac_People`.
+const CONTACT_TABLES: [(&str, &str); 2] =
+    [("fac_Contact", "ContactID"), ("fac_People", "PersonID")];
+
+/// Reads a text column, treating `NULL` like an empty string.
+fn text(row: &MySqlRow, column: &str) -> Result<String, Error> {
+    let value: Option<String> = row.try_get(column)?;
+
+    Ok(value.unwrap_or_default().trim().to_owned())
+}
+
+/// Reads a date column cast to text. openDCIM stores unset dates as
+/// `0000-00-00` or `NULL`.
+fn date(row: &MySqlRow, column: &str) -> Result<Option<String>, Error> {
+    let value = text(row, column)?;
+
+    Ok(match value.as_str() {
+        "" | "0000-00-00" => None,
+        _ => Some(value),
+    })
+}
+
+impl TryFrom<MySqlRow> for Contact {
+    type Error = Error;
+
+    fn try_from(row: MySqlRow) -> Result<Self, Error> {
+        let name = format!("{} {}", text(&row, "FirstName")?, text(&row, "LastName")?);
+
+        Ok(Contact {
+            name: name.trim().to_owned(),
+            phone: text(&row, "Phone1")?,
+            email: text(&row, "Email")?,
+        })
+    }
+}
+
+impl TryFrom<MySqlRow> for DeviceDetails {
+    type Error = Error;
+
+    fn try_from(row: MySqlRow) -> Result<Self, Error> {
+        Ok(DeviceDetails {
+            manufacturer: text(&row, "Manufacturer")?,
+            model: text(&row, "Model")?,
+            serial_no: text(&row, "SerialNo")?,
+            asset_tag: text(&row, "AssetTag")?,
+            install_date: date(&row, "InstallDate")?,
+            warranty_expire: date(&row, "WarrantyExpire")?,
+            department: text(&row, "Department")?,
+            contact: None,
+        })
+    }
+}
+
+/// Reads the contacts with the given IDs, keyed by ID. Databases without
+/// any of the [`CONTACT_TABLES`] have no contacts.
+async fn fetch_contacts(
+    conn: &mut MySqlConnection,
+    contact_ids: &[i32],
+) -> Result<HashMap<i32, Contact>, Error> {
+    if contact_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = vec!["?"; contact_ids.len()].join(", ");
+    for (table, id) in CONTACT_TABLES {
+        let sql = format!(
+            "SELECT {id} AS ID, FirstName, LastName, Phone1, Email FROM {table} \
+             WHERE {id} IN ({placeholders})"
+        );
+        let mut query = sqlx::query(&sql);
+        for contact_id in contact_ids {
+            query = query.bind(contact_id);
+        }
+        let rows = match query.fetch_all(&mut *conn).await.map_err(Error::from) {
+            Ok(rows) => rows,
+            Err(Error::Schema(_)) => continue,
+            Err(error) => return Err(error),
+        };
+
+        let mut contacts = HashMap::new();
+        for row in rows {
+            let contact_id: i32 = row.try_get("ID")?;
+            let contact = Contact::try_from(row)?;
+            if contact != Contact::default() {
+                contacts.insert(contact_id, contact);
+            }
+        }
+        return Ok(contacts);
+    }
+
+    Ok(HashMap::new())
+}
+
+/// Reads the details of every device in `device_ids`, keyed by `DeviceID`.
+/// The contacts are read separately, so the other details show even if
+/// neither contact table exists.
+pub(crate) async fn fetch_details(
+    conn: &mut MySqlConnection,
+    device_ids: &[i32],
+) -> Result<HashMap<i32, DeviceDetails>, Error> {
+    let mut device_ids = device_ids.to_vec();
+    device_ids.sort_unstable();
+    device_ids.dedup();
+    if device_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let sql = format!(
+        r#"
+SELECT d.DeviceID, d.SerialNo, d.AssetTag,
+  CAST(d.InstallDate AS CHAR) AS InstallDate,
+  CAST(d.WarrantyExpire AS CHAR) AS WarrantyExpire,
+  d.PrimaryContact, m.Name AS Manufacturer, t.Model, dp.Name AS Department
+FROM fac_Device d
+  LEFT JOIN fac_DeviceTemplate t ON t.TemplateID = d.TemplateID
+  LEFT JOIN fac_Manufacturer m ON m.ManufacturerID = t.ManufacturerID
+  LEFT JOIN fac_Department dp ON dp.DeptID = d.Owner
+WHERE d.DeviceID IN ({})
+"#,
+        vec!["?"; device_ids.len()].join(", ")
+    );
+    let mut query = sqlx::query(&sql);
+    for device_id in &device_ids {
+        query = query.bind(device_id);
+    }
+
+    let mut details = HashMap::new();
+    let mut contact_ids = Vec::new();
+    for row in query.fetch_all(&mut *conn).await? {
+        let device_id: i32 = row.try_get("DeviceID")?;
+        let contact_id: Option<i32> = row.try_get("PrimaryContact")?;
+        let contact_id = contact_id.filter(|&contact_id| contact_id > 0);
+        contact_ids.extend(contact_id);
+        details.insert(device_id, (contact_id, DeviceDetails::try_from(row)?));
+    }
+
+    contact_ids.sort_unstable();
+    contact_ids.dedup();
+    let contacts = fetch_contacts(conn, &contact_ids).await?;
+
+    Ok(details
+        .into_iter()
+        .map(|(device_id, (contact_id, mut details))| {
+            details.contact = contact_id.and_then(|id| contacts.get(&id).cloned());
+            (device_id, details)
+        })
+        .collect())
+}
@@ -0,0 +1,329 @@
+use super::{device, naming, trace, uplink};
+use crate::{
+    naming::IdColumn, suggest, Error, NamingScheme, PortLookup, RoomId, SocketId, Uplink, MAX_HOPS,
+};
+use sqlx::MySqlConnection;
+
+/// Looks up every name, giving up on paths longer than `max_hops`, see
+/// [`DcimBackend::lookup_many`](crate::DcimBackend::lookup_many).
+pub(crate) async fn lookup_many(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+    names: &[&str],
+    max_hops: usize,
+) -> Result<Vec<Result<PortLookup, Error>>, Error> {
+    let columns = crate::naming::columns(naming);
+    let names: Vec<&str> = names.iter().map(|name| name.trim()).collect();
+    let sockets: Vec<Option<SocketId>> = names.iter().map(|name| name.parse().ok()).collect();
+    let canonical: Vec<Option<String>> = sockets
+        .iter()
+        .map(|socket| socket.as_ref().map(SocketId::to_string))
+        .collect();
+
+    // Try every name as given first, then in its canonical form.
+    let mut starts: Vec<Vec<trace::PortRow>> = names.iter().map(|_| Vec::new()).collect();
+    let wanted: Vec<&str> = names
+        .iter()
+        .copied()
+        .filter(|name| !name.is_empty())
+        .collect();
+    let rows = trace::fetch_named_many(conn, &wanted, &columns).await?;
+    assign(&mut starts, &names, &rows, &columns);
+
+    // Names already given in canonical form were looked for above.
+    let retry: Vec<&str> = (0..names.len())
+        .map(|index| match &canonical[index] {
+            Some(name) if starts[index].is_empty() && name != names[index] => name.as_str(),
+            _ => "",
+        })
+        .collect();
+    if retry.iter().any(|name| !name.is_empty()) {
+        let wanted: Vec<&str> = retry
+            .iter()
+            .copied()
+            .filter(|name| !name.is_empty())
+            .collect();
+        let rows = trace::fetch_named_many(conn, &wanted, &columns).await?;
+        assign(&mut starts, &retry, &rows, &columns);
+    }
+
+    // A single outlet may share its physical port with others, look for
+    // those once per room.
+    let mut rooms: Vec<&RoomId> = (0..names.len())
+        .filter(|&index| starts[index].is_empty())
+        .filter_map(|index| sockets[index].as_ref().map(SocketId::room_id))
+        .collect();
+    rooms.sort();
+    rooms.dedup();
+    for room in rooms {
+        let rows = trace::fetch_room(conn, room, &columns).await?;
+        for (index, socket) in sockets.iter().enumerate() {
+            let Some(socket) = socket.as_ref().filter(|socket| socket.room_id() == room) else {
+                continue;
+            };
+            if !starts[index].is_empty() {
+                continue;
+            }
+            starts[index].extend(rows.iter().filter(|row| row.hop.covers(socket)).cloned());
+        }
+    }
+
+    // Follow every path of the batch side by side.
+    let counts: Vec<usize> = starts.iter().map(Vec::len).collect();
+    let starts: Vec<trace::PortRow> = starts.into_iter().flatten().collect();
+    let mut paths = trace::follow_many(conn, starts, max_hops)
+        .await?
+        .into_iter();
+
+    // Collect the lookups of all names in one list to classify them at
+    // once, remembering how many belong to each name.
+    let mut identifiers = None;
+    let mut lookups = Vec::new();
+    let mut outcomes = Vec::new();
+    for (index, &count) in counts.iter().enumerate() {
+        let mut traced: Vec<_> = paths.by_ref().take(count).collect();
+        let outcome = match count {
+            0 => {
+                if identifiers.is_none() {
+                    identifiers = Some(naming::identifiers(conn, naming).await?);
+                }
+                let query = canonical[index].as_deref().unwrap_or(names[index]);
+                let candidates = identifiers.iter().flatten().map(|id| id.name.as_str());
+                Err(Error::NotFound {
+                    query: names[index].to_owned(),
+                    suggestions: suggest::closest(query, candidates),
+                })
+            }
+            1 => traced.remove(0).and_then(lookup_from_path).map(|lookup| {
+                lookups.push(lookup);
+                1
+            }),
+            _ => {
+                // Several ports carry the same description. Only those
+                // patched through to a switch are a conflict, collect them
+                // all so the user can sort it out.
+                let before = lookups.len();
+                let mut broken = None;
+                for path in traced {
+                    match path.and_then(lookup_from_path) {
+                        Ok(lookup) => lookups.push(lookup),
+                        Err(error) => {
+                            broken.get_or_insert(error);
+                        }
+                    }
+                }
+                match (lookups.len() - before, broken) {
+                    (0, Some(error)) => Err(error),
+                    (traced, _) => Ok(traced),
+                }
+            }
+        };
+        outcomes.push(outcome);
+    }
+    complete(conn, naming, &mut lookups).await?;
+
+    let mut lookups = lookups.into_iter();
+    let results = outcomes
+        .into_iter()
+        .enumerate()
+        .map(|(index, outcome)| {
+            // Ports that aren't patched through don't count as a conflict.
+            let mut candidates: Vec<PortLookup> = lookups.by_ref().take(outcome?).collect();
+            match candidates.len() {
+                1 => Ok(candidates.remove(0)),
+                count => Err(Error::Ambiguous {
+                    query: names[index].to_owned(),
+                    count,
+                    candidates,
+                }),
+            }
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Adds every row to the starts of the names it is found for. Empty names
+/// are skipped.
+fn assign(
+    starts: &mut [Vec<trace::PortRow>],
+    names: &[&str],
+    rows: &[trace::PortRow],
+    columns: &[IdColumn],
+) {
+    for (starts, name) in starts.iter_mut().zip(names) {
+        if name.is_empty() {
+            continue;
+        }
+        starts.extend(
+            rows.iter()
+                .filter(|row| row.hop.is_named(name, columns))
+                .cloned(),
+        );
+    }
+}
+
+/// Looks up the wallsocket patched to a switch port, see
+/// [`DcimBackend::reverse_lookup`](crate::DcimBackend::reverse_lookup).
+pub(crate) async fn reverse_lookup(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+    switch: &str,
+    port: &str,
+) -> Result<PortLookup, Error> {
+    let start = trace::fetch_switchport(conn, switch, port).await?;
+    let path = trace::follow_back_many(conn, vec![start], MAX_HOPS)
+        .await?
+        .remove(0)?;
+    let mut lookup = lookup_from_path(path)?;
+    complete(conn, naming, std::slice::from_mut(&mut lookup)).await?;
+
+    Ok(lookup)
+}
+
+/// Lists every wallsocket patched to a switch, see
+/// [`DcimBackend::switch_sockets`](crate::DcimBackend::switch_sockets).
+pub(crate) async fn switch_sockets(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+    switch: &str,
+) -> Result<Vec<PortLookup>, Error> {
+    let ports = trace::fetch_switchports(conn, switch).await?;
+    let mut lookups = Vec::new();
+    for path in trace::follow_back_many(conn, ports, MAX_HOPS).await? {
+        match path.and_then(lookup_from_path) {
+            Ok(lookup) => lookups.push(lookup),
+            Err(Error::NotFound { .. } | Error::BrokenPath(_)) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    complete(conn, naming, &mut lookups).await?;
+
+    Ok(lookups)
+}
+
+/// Follows the uplinks of a switch to the core switches matching `core`,
+/// see [`DcimBackend::uplinks`](crate::DcimBackend::uplinks).
+pub(crate) async fn uplinks(
+    conn: &mut MySqlConnection,
+    switch: &str,
+    core: Option<&str>,
+) -> Result<Vec<Uplink>, Error> {
+    let switch = trace::fetch_switch(conn, switch).await?;
+
+    uplink::uplinks(conn, switch, core).await
+}
+
+/// Lists every wallsocket in a room, see
+/// [`DcimBackend::room_sockets`](crate::DcimBackend::room_sockets).
+pub(crate) async fn room_sockets(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+    room: &str,
+) -> Result<Vec<PortLookup>, Error> {
+    let room: RoomId = room.parse()?;
+    let rows = trace::fetch_room(conn, &room, &crate::naming::columns(naming)).await?;
+    if rows.is_empty() {
+        return Err(Error::not_found(room.to_string()));
+    }
+
+    let mut lookups = Vec::new();
+    for path in trace::follow_many(conn, rows, MAX_HOPS).await? {
+        // A single broken wallsocket shouldn't hide the rest of the room.
+        match path.and_then(lookup_from_path) {
+            Ok(lookup) => lookups.push(lookup),
+            Err(Error::BrokenPath(_)) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    complete(conn, naming, &mut lookups).await?;
+
+    Ok(lookups)
+}
+
+/// Fills in what isn't read while following the paths: the kind and column
+/// of every lookup's wallsocket and the details of its switch.
+async fn complete(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+    lookups: &mut [PortLookup],
+) -> Result<(), Error> {
+    classify(conn, naming, lookups).await?;
+
+    // The details are a nice to have, openDCIM versions laid out differently
+    // shouldn't break lookups.
+    let device_ids: Vec<i32> = lookups
+        .iter()
+        .map(|lookup| lookup.switch_device_id)
+        .collect();
+    let details = match device::fetch_details(conn, &device_ids).await {
+        Ok(details) => details,
+        Err(Error::Schema(_)) => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    for lookup in lookups.iter_mut() {
+        lookup.switch_details = details.get(&lookup.switch_device_id).cloned();
+    }
+
+    Ok(())
+}
+
+/// Sets the kind of every lookup's wallsocket according to `naming`, along
+/// with the column its description is taken from: the first column whose
+/// identifier matches a naming scheme for it, or else the first one holding
+/// an identifier at all.
+async fn classify(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+    lookups: &mut [PortLookup],
+) -> Result<(), Error> {
+    let columns = crate::naming::columns(naming);
+    let names: Vec<(IdColumn, &str)> = lookups
+        .iter()
+        .flat_map(|lookup| {
+            let hop = lookup.path.first();
+            columns
+                .iter()
+                .map(move |&column| (column, hop.map_or("", |hop| hop.identifier(column))))
+        })
+        .collect();
+    let kinds = naming::classify(conn, naming, &names).await?;
+
+    for (lookup, kinds) in lookups.iter_mut().zip(kinds.chunks(columns.len().max(1))) {
+        let matched = columns
+            .iter()
+            .zip(kinds)
+            .find_map(|(&column, kind)| Some((column, (*kind)?)));
+        (lookup.id_column, lookup.kind) = match matched {
+            Some((column, kind)) => (Some(column), Some(kind)),
+            None => {
+                let hop = lookup.path.first();
+                (hop.and_then(|hop| hop.id_column(&columns)), None)
+            }
+        };
+    }
+
+    Ok(())
+}
+
+/// Builds a [`PortLookup`] from a path ending at a switch port.
+fn lookup_from_path(path: Vec<trace::PortRow>) -> Result<PortLookup, Error> {
+    let (first, last) = match (path.first(), path.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(Error::BrokenPath("empty path".to_owned())),
+    };
+
+    Ok(PortLookup {
+        switch_label: last.hop.device_label.clone(),
+        switch_ip: last.primary_ip.trim().parse().ok(),
+        switch_port: last.hop.port_label.clone(),
+        port_notes: last.hop.port_notes.clone(),
+        switch_device_id: last.hop.device_id,
+        patchpanel_device_id: first.hop.device_id,
+        patchpanel_port_number: first.hop.port_number,
+        path: path.into_iter().map(|row| row.hop).collect(),
+        kind: None,
+        id_column: None,
+        switch_details: None,
+    })
+}
@@ -0,0 +1,100 @@
+use super::trace::CHUNK_SIZE;
+use crate::{
+    naming::{self, IdColumn},
+    Error, Identifier, NamingScheme, PortKind,
+};
+use sqlx::{MySqlConnection, Row};
+
+/// Returns the kind of the first naming scheme each of `names` matches, or
+/// `None` for names matching no scheme. Every name is only matched against
+/// the schemes for the column it was read from, empty names match nothing.
+pub(crate) async fn classify(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+    names: &[(IdColumn, &str)],
+) -> Result<Vec<Option<PortKind>>, Error> {
+    if naming.is_empty() || names.is_empty() {
+        return Ok(vec![None; names.len()]);
+    }
+
+    // One CASE per name, each returning the index of the first matching
+    // scheme. The casts keep MySQL from treating the parameters as binary.
+    // Every name binds two parameters per scheme, so the names are split
+    // up to stay below the placeholder limit.
+    let mut kinds = Vec::with_capacity(names.len());
+    for chunk in names.chunks((CHUNK_SIZE / naming.len()).max(1)) {
+        let mut cases = Vec::new();
+        let mut params = Vec::new();
+        for (index, &(column, name)) in chunk.iter().enumerate() {
+            let mut case = String::new();
+            for (scheme_index, scheme) in naming.iter().enumerate() {
+                if scheme.column != column || name.trim().is_empty() {
+                    continue;
+                }
+                case +=
+                    &format!(" WHEN CAST(? AS CHAR) REGEXP CAST(? AS CHAR) THEN {scheme_index}");
+                params.push((name, scheme.pattern.as_str()));
+            }
+            cases.push(match case.is_empty() {
+                true => format!("NULL AS k{index}"),
+                false => format!("CASE{case} END AS k{index}"),
+            });
+        }
+
+        let sql = format!("SELECT {}", cases.join(", "));
+        let mut query = sqlx::query(&sql);
+        for (name, pattern) in params {
+            query = query.bind(name).bind(pattern);
+        }
+        let row = query.fetch_one(&mut *conn).await?;
+
+        for index in 0..chunk.len() {
+            let scheme: Option<i64> = row.try_get(index)?;
+            kinds.push(scheme.map(|scheme| naming[scheme as usize].kind));
+        }
+    }
+
+    Ok(kinds)
+}
+
+/// Reads all port identifiers matching one of `naming`, see
+/// [`DcimBackend::identifiers`](crate::DcimBackend::identifiers).
+pub(crate) async fn identifiers(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+) -> Result<Vec<Identifier>, Error> {
+    if naming.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // One SELECT per scheme, tagged with its index to look up the kind.
+    let sql = naming
+        .iter()
+        .enumerate()
+        .map(|(index, scheme)| {
+            let column = scheme.column.sql();
+            format!(
+                "SELECT {column} AS Name, {index} AS Scheme FROM fac_Ports WHERE {column} REGEXP ?"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    let mut query = sqlx::query(&sql);
+    for scheme in naming {
+        query = query.bind(&scheme.pattern);
+    }
+
+    let mut matches = Vec::new();
+    for row in query.fetch_all(&mut *conn).await? {
+        let name: String = row.try_get("Name")?;
+        let scheme: i64 = row.try_get("Scheme")?;
+        matches.push((scheme, name));
+    }
+
+    // Keep the first matching scheme of every identifier.
+    matches.sort_unstable_by_key(|(scheme, _)| *scheme);
+
+    Ok(naming::collect(matches.into_iter().map(
+        |(scheme, name)| (name, naming[scheme as usize].kind),
+    )))
+}
@@ -0,0 +1,24 @@
+use super::naming;
+use crate::{search, Error, NamingScheme, SearchIndex};
+use sqlx::{MySqlConnection, Row};
+
+/// Reads every port identifier matching one of `naming` and every switch,
+/// see [`DcimBackend::search_index`](crate::DcimBackend::search_index).
+pub(crate) async fn index(
+    conn: &mut MySqlConnection,
+    naming: &[NamingScheme],
+) -> Result<SearchIndex, Error> {
+    let identifiers = naming::identifiers(conn, naming).await?;
+
+    let rows = sqlx::query(
+        "SELECT Label, PrimaryIP FROM fac_Device WHERE DeviceType = 'Switch' ORDER BY Label",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut switches = Vec::new();
+    for row in rows {
+        switches.push((row.try_get("Label")?, row.try_get("PrimaryIP")?));
+    }
+
+    Ok(search::build(identifiers, switches))
+}
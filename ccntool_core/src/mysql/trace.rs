@@ -0,0 +1,802 @@
+use crate::{
+    backend::BoxFuture,
+    naming::IdColumn,
+    trace::{share_cable, Chassis, Hop, Location},
+    Error, RoomId, SocketId,
+};
+use sqlx::{mysql::MySqlRow, MySqlConnection, Row};
+use std::collections::{HashMap, HashSet};
+
+/// Maximum number of keys bound to a single query, well below the limit of
+/// 65535 placeholders per prepared statement.
+pub(crate) const CHUNK_SIZE: usize = 1000;
+
+/// Ports keyed by `(DeviceID, PortNumber)`.
+pub(crate) type PortMap = HashMap<(i32, i32), PortRow>;
+
+/// A port as read from the database, with everything needed to follow its
+/// connection.
+#[derive(Clone)]
+pub(crate) struct PortRow {
+    pub(crate) hop: Hop,
+    pub(crate) device_type: String,
+    /// `PrimaryIP` of the device, or of its chassis if it has none.
+    pub(crate) primary_ip: String,
+    pub(crate) connected: Option<(i32, i32)>,
+}
+
+impl PortRow {
+    /// Patch panels are passive, everything else ends the path.
+    pub(crate) fn is_patchpanel(&self) -> bool {
+        self.device_type == "Patch Panel"
+    }
+
+    fn key(&self) -> (i32, i32) {
+        (self.hop.device_id, self.hop.port_number)
+    }
+}
+
+impl TryFrom<MySqlRow> for PortRow {
+    type Error = Error;
+
+    fn try_from(row: MySqlRow) -> Result<Self, Error> {
+        let connected_device: Option<i32> = row.try_get("ConnectedDeviceID")?;
+        let connected_port: Option<i32> = row.try_get("ConnectedPort")?;
+        let name = |column: &str| -> Result<Option<String>, Error> {
+            let value: Option<String> = row.try_get(column)?;
+            Ok(value
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty()))
+        };
+        let parent: Option<i32> = row.try_get("ParentID")?;
+        let parent_ip: String = name("ParentIP")?.unwrap_or_default();
+        let chassis = match parent {
+            Some(device_id) => Some(Chassis {
+                device_id,
+                label: row.try_get("ParentLabel")?,
+                primary_ip: parent_ip.parse().ok(),
+                slot: row.try_get("Slot")?,
+            }),
+            None => None,
+        };
+        let primary_ip: String = row.try_get("PrimaryIP")?;
+        let primary_ip = match primary_ip.trim() {
+            "" => parent_ip,
+            own => own.to_owned(),
+        };
+
+        let cabinet: Option<String> = row.try_get("CabinetLocation")?;
+        let location = match cabinet {
+            Some(cabinet) => Some(Location {
+                data_center: row
+                    .try_get::<Option<String>, _>("DataCenterName")?
+                    .unwrap_or_default(),
+                cabinet,
+                position: row.try_get::<Option<i32>, _>("Position")?.unwrap_or(0),
+                height: row.try_get::<Option<i32>, _>("Height")?.unwrap_or(0),
+            }),
+            None => None,
+        };
+
+        Ok(PortRow {
+            hop: Hop {
+                device_id: row.try_get("DeviceID")?,
+                device_label: row.try_get("DeviceLabel")?,
+                port_number: row.try_get("PortNumber")?,
+                port_label: row.try_get("Label")?,
+                port_notes: row.try_get("Notes")?,
+                location,
+                media_type: name("MediaType")?,
+                color: name("ColorName")?,
+                chassis,
+            },
+            device_type: row.try_get("DeviceType")?,
+            primary_ip,
+            connected: connected_device.zip(connected_port),
+        })
+    }
+}
+
+/// Child devices like stack members and line cards take their rack position
+/// from their chassis, their own `Position` is the slot.
+pub(crate) const PORT_COLUMNS: &str = r#"
+SELECT p.DeviceID, p.PortNumber, p.Label, p.Notes, p.ConnectedDeviceID,
+  p.ConnectedPort, d.Label AS DeviceLabel, d.DeviceType, d.PrimaryIP,
+  pd.DeviceID AS ParentID, pd.Label AS ParentLabel, pd.PrimaryIP AS ParentIP,
+  d.Position AS Slot, COALESCE(pd.Position, d.Position) AS Position,
+  COALESCE(pd.Height, d.Height) AS Height, c.Location AS CabinetLocation,
+  dc.Name AS DataCenterName, mt.MediaType, cc.Name AS ColorName
+FROM fac_Ports p
+  JOIN fac_Device d ON d.DeviceID = p.DeviceID
+  LEFT JOIN fac_Device pd ON pd.DeviceID = d.ParentDevice
+  LEFT JOIN fac_Cabinet c ON c.CabinetID = COALESCE(pd.Cabinet, d.Cabinet)
+  LEFT JOIN fac_DataCenter dc ON dc.DataCenterID = c.DataCenterID
+  LEFT JOIN fac_MediaTypes mt ON mt.MediaID = p.MediaID
+  LEFT JOIN fac_ColorCoding cc ON cc.ColorID = p.ColorID
+"#;
+
+/// Reads the ports with the given `(DeviceID, PortNumber)` keys, or the
+/// ports connected to them if `connected` is set, in as few queries as
+/// possible. The result is keyed by the requested port.
+pub(crate) async fn fetch_ports(
+    conn: &mut MySqlConnection,
+    keys: &[(i32, i32)],
+    connected: bool,
+) -> Result<PortMap, Error> {
+    let columns = match connected {
+        false => "(p.DeviceID, p.PortNumber)",
+        true => "(p.ConnectedDeviceID, p.ConnectedPort)",
+    };
+
+    let mut ports = HashMap::new();
+    for chunk in keys.chunks(CHUNK_SIZE) {
+        let placeholders = vec!["(?, ?)"; chunk.len()].join(", ");
+        let sql = format!(
+            "{PORT_COLUMNS} WHERE {columns} IN ({placeholders}) ORDER BY p.DeviceID, p.PortNumber"
+        );
+        let mut query = sqlx::query(&sql);
+        for (device_id, port_number) in chunk {
+            query = query.bind(device_id).bind(port_number);
+        }
+
+        for row in query.fetch_all(&mut *conn).await? {
+            let row = PortRow::try_from(row)?;
+            let key = match connected {
+                false => Some(row.key()),
+                true => row.connected,
+            };
+            if let Some(key) = key {
+                ports.entry(key).or_insert(row);
+            }
+        }
+    }
+
+    Ok(ports)
+}
+
+/// Reads every patch panel port whose identifier in one of `columns` is one
+/// of `names`, in as few queries as possible. Use [`Hop::is_named`] to
+/// tell which name a port was found for. Switch ports repeating the
+/// wallsocket in their description are no place to start from.
+pub(crate) async fn fetch_named_many(
+    conn: &mut MySqlConnection,
+    names: &[&str],
+    columns: &[IdColumn],
+) -> Result<Vec<PortRow>, Error> {
+    let mut rows = Vec::new();
+    if columns.is_empty() {
+        return Ok(rows);
+    }
+
+    for chunk in names.chunks(CHUNK_SIZE) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let condition = columns
+            .iter()
+            .map(|column| format!("p.{} IN ({placeholders})", column.sql()))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let sql = format!(
+            "{PORT_COLUMNS} WHERE d.DeviceType = 'Patch Panel' AND ({condition}) \
+             ORDER BY p.DeviceID, p.PortNumber"
+        );
+        let mut query = sqlx::query(&sql);
+        for _ in columns {
+            for name in chunk {
+                query = query.bind(name.trim());
+            }
+        }
+
+        for row in query.fetch_all(&mut *conn).await? {
+            rows.push(PortRow::try_from(row)?);
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Reads every patch panel port in `room`, i.e. whose identifier in one of
+/// `columns` is a [`SocketId`] in the room, ordered by identifier.
+pub(crate) async fn fetch_room(
+    conn: &mut MySqlConnection,
+    room: &RoomId,
+    columns: &[IdColumn],
+) -> Result<Vec<PortRow>, Error> {
+    let condition = columns
+        .iter()
+        .map(|column| format!("p.{} LIKE ?", column.sql()))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    if condition.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Identifiers may be stored with or without leading zeros, so only
+    // narrow the ports down by room number and sort them out once parsed.
+    let sql = format!("{PORT_COLUMNS} WHERE d.DeviceType = 'Patch Panel' AND ({condition})");
+    let pattern = format!("%.%.%{}%-%", room.room());
+    let mut query = sqlx::query(&sql);
+    for _ in columns {
+        query = query.bind(&pattern);
+    }
+
+    let mut rows = Vec::new();
+    for row in query.fetch_all(&mut *conn).await? {
+        let row = PortRow::try_from(row)?;
+        let socket = [&row.hop.port_notes, &row.hop.port_label]
+            .into_iter()
+            .find_map(|name| name.parse::<SocketId>().ok());
+        if let Some(socket) = socket.filter(|socket| socket.room_id() == room) {
+            rows.push((socket, row));
+        }
+    }
+    rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(rows.into_iter().map(|(_, row)| row).collect())
+}
+
+/// Where the ports of a path are read from. Paths are followed over the
+/// database, but the same steps run over rows held in memory.
+pub(crate) trait PortSource: Send {
+    /// Reads the ports with the given keys, or the ports connected to them,
+    /// see [`fetch_ports`].
+    fn ports<'a>(
+        &'a mut self,
+        keys: &'a [(i32, i32)],
+        connected: bool,
+    ) -> BoxFuture<'a, Result<PortMap, Error>>;
+}
+
+impl PortSource for MySqlConnection {
+    fn ports<'a>(
+        &'a mut self,
+        keys: &'a [(i32, i32)],
+        connected: bool,
+    ) -> BoxFuture<'a, Result<PortMap, Error>> {
+        Box::pin(fetch_ports(self, keys, connected))
+    }
+}
+
+/// A path being followed, along with every port it has passed.
+struct Walk {
+    path: Vec<PortRow>,
+    seen: HashSet<(i32, i32)>,
+}
+
+impl Walk {
+    fn new(start: PortRow) -> Self {
+        Walk {
+            seen: HashSet::from([start.key()]),
+            path: vec![start],
+        }
+    }
+
+    fn last(&self) -> &PortRow {
+        &self.path[self.path.len() - 1]
+    }
+
+    /// Appends `port`, failing if the path has passed it before.
+    fn push(&mut self, port: PortRow) -> Result<(), Error> {
+        if !self.seen.insert(port.key()) {
+            return Err(Error::BrokenPath(format!(
+                "loop detected at {} port {}",
+                port.hop.device_label, port.hop.port_label
+            )));
+        }
+
+        self.path.push(port);
+        Ok(())
+    }
+
+    /// Appends `port`, the other end of the cable plugged into the last port.
+    fn connect(&mut self, mut port: PortRow) -> Result<(), Error> {
+        let last = self.path.len() - 1;
+        share_cable(&mut self.path[last].hop, &mut port.hop);
+        self.push(port)
+    }
+}
+
+/// Reads the port on the other end of the cable plugged into each of
+/// `ports`, in as few queries as possible.
+///
+/// openDCIM usually stores a connection on both ports, but fall back to
+/// searching for a port pointing at the port in case only one side is set.
+async fn fetch_connected<S: PortSource + ?Sized>(
+    source: &mut S,
+    ports: &[&PortRow],
+) -> Result<Vec<Option<PortRow>>, Error> {
+    let connected: Vec<_> = ports.iter().filter_map(|port| port.connected).collect();
+    let unconnected: Vec<_> = ports
+        .iter()
+        .filter(|port| port.connected.is_none())
+        .map(|port| port.key())
+        .collect();
+    let peers = source.ports(&connected, false).await?;
+    let referencing = source.ports(&unconnected, true).await?;
+
+    Ok(ports
+        .iter()
+        .map(|port| match port.connected {
+            Some(key) => peers.get(&key).cloned(),
+            None => referencing.get(&port.key()).cloned(),
+        })
+        .collect())
+}
+
+/// Takes a step on each of the `active` paths, which end at a patch panel
+/// port: on to the opposite port of the patch panel, as it usually holds the
+/// cable to the next device, and reads the port on the other end of that
+/// cable. A path fails if it would pass more than `max_hops` ports, its
+/// patch panel port has no opposite port or it runs in a loop.
+///
+/// Returns the index of every path stepped on, along with the port
+/// connected to its new last port.
+async fn step<S: PortSource + ?Sized>(
+    source: &mut S,
+    walks: &mut [Result<Walk, Error>],
+    active: Vec<usize>,
+    max_hops: usize,
+) -> Result<Vec<(usize, Option<PortRow>)>, Error> {
+    // Front and rear port of a patch panel share the absolute port number.
+    let mut pending = Vec::new();
+    for index in active {
+        let Ok(walk) = &walks[index] else { continue };
+        let current = &walk.last().hop;
+        if walk.path.len() + 2 > max_hops {
+            let error = Error::BrokenPath(format!(
+                "gave up after {max_hops} ports at {} port {}",
+                current.device_label, current.port_label
+            ));
+            walks[index] = Err(error);
+            continue;
+        }
+        pending.push((index, (current.device_id, -current.port_number)));
+    }
+    let keys: Vec<_> = pending.iter().map(|(_, key)| *key).collect();
+    let pairs = source.ports(&keys, false).await?;
+
+    let mut stepped = Vec::new();
+    for (index, key) in pending {
+        let Ok(walk) = &mut walks[index] else {
+            continue;
+        };
+        let result = match pairs.get(&key) {
+            Some(pair) => walk.push(pair.clone()),
+            None => Err(Error::BrokenPath(format!(
+                "{} port {} has no opposite port",
+                walk.last().hop.device_label,
+                walk.last().hop.port_label
+            ))),
+        };
+        match result {
+            Ok(()) => stepped.push(index),
+            Err(error) => walks[index] = Err(error),
+        }
+    }
+
+    let ends: Vec<_> = stepped
+        .iter()
+        .filter_map(|&index| walks[index].as_ref().ok())
+        .map(Walk::last)
+        .collect();
+    let next = fetch_connected(source, &ends).await?;
+
+    Ok(stepped.into_iter().zip(next).collect())
+}
+
+/// Follows the paths from `starts` through front/rear port pairs and their
+/// connections until each reaches a device that isn't a patch panel. Both
+/// ports of every patch panel passed are part of the path, as the rear one
+/// usually holds the cable to the next device. All
+/// paths are followed side by side, reading the next hop of every path with
+/// a single query. The result has one entry per start; only database errors
+/// fail the whole batch.
+pub(crate) async fn follow_many<S: PortSource + ?Sized>(
+    source: &mut S,
+    starts: Vec<PortRow>,
+    max_hops: usize,
+) -> Result<Vec<Result<Vec<PortRow>, Error>>, Error> {
+    let mut walks: Vec<Result<Walk, Error>> = starts
+        .into_iter()
+        .map(|start| Ok(Walk::new(start)))
+        .collect();
+
+    loop {
+        // Paths still ending at a patch panel need another hop.
+        let active: Vec<_> = walks
+            .iter()
+            .enumerate()
+            .filter(|(_, walk)| {
+                walk.as_ref()
+                    .map_or(false, |walk| walk.last().is_patchpanel())
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if active.is_empty() {
+            return Ok(walks
+                .into_iter()
+                .map(|walk| walk.map(|walk| walk.path))
+                .collect());
+        }
+
+        for (index, next) in step(source, &mut walks, active, max_hops).await? {
+            let Ok(walk) = &mut walks[index] else {
+                continue;
+            };
+            let result = match next {
+                Some(next) => walk.connect(next),
+                None => Err(Error::BrokenPath(format!(
+                    "{} port {} is not connected",
+                    walk.last().hop.device_label,
+                    walk.last().hop.port_label
+                ))),
+            };
+            if let Err(error) = result {
+                walks[index] = Err(error);
+            }
+        }
+    }
+}
+
+/// Follows the paths backwards from the switch ports `starts` through patch
+/// panels until each reaches a port nothing else is patched to, which is
+/// the wallsocket. Like [`follow_many`], all paths are followed side by
+/// side and the result has one entry per start, each ordered from
+/// wallsocket to switch.
+///
+/// Paths ending at another device, e.g. an uplink patched through a fiber
+/// panel, don't lead to a wallsocket and are reported as not found, just
+/// like a switch port cabled to the device directly.
+pub(crate) async fn follow_back_many<S: PortSource + ?Sized>(
+    source: &mut S,
+    starts: Vec<PortRow>,
+    max_hops: usize,
+) -> Result<Vec<Result<Vec<PortRow>, Error>>, Error> {
+    let mut walks: Vec<Result<Walk, Error>> = starts
+        .into_iter()
+        .map(|start| Ok(Walk::new(start)))
+        .collect();
+    let switch_ports: Vec<_> = walks
+        .iter()
+        .filter_map(|walk| walk.as_ref().ok())
+        .map(Walk::last)
+        .collect();
+    let mut next: Vec<_> = (0..)
+        .zip(fetch_connected(source, &switch_ports).await?)
+        .collect();
+
+    loop {
+        // Paths whose last port is patched to a patch panel need another hop.
+        let mut active = Vec::new();
+        for (index, next) in next {
+            let Ok(walk) = &mut walks[index] else {
+                continue;
+            };
+            let result = match next {
+                None => continue,
+                Some(peer) if peer.is_patchpanel() => walk.connect(peer),
+                Some(_) => Err(not_patched(&walk.path[0])),
+            };
+            match result {
+                Ok(()) => active.push(index),
+                Err(error) => walks[index] = Err(error),
+            }
+        }
+        if active.is_empty() {
+            break;
+        }
+        next = step(source, &mut walks, active, max_hops).await?;
+    }
+
+    Ok(walks
+        .into_iter()
+        .map(|walk| {
+            let walk = walk?;
+            if walk.path.len() == 1 {
+                return Err(not_patched(&walk.path[0]));
+            }
+
+            let mut path = walk.path;
+            path.reverse();
+            Ok(path)
+        })
+        .collect())
+}
+
+/// The error for a switch port that isn't patched to a wallsocket.
+fn not_patched(switch_port: &PortRow) -> Error {
+    Error::not_found(format!(
+        "{} {}",
+        switch_port.hop.device_label, switch_port.hop.port_label
+    ))
+}
+
+/// Finds the `DeviceID` of the switch with the given label, IP address or
+/// `DeviceID`. Stack members sharing the IP address of their stack give way
+/// to the stack.
+pub(crate) async fn fetch_switch(conn: &mut MySqlConnection, switch: &str) -> Result<i32, Error> {
+    let switch = switch.trim();
+    if switch.is_empty() {
+        return Err(Error::MissingSwitch);
+    }
+
+    let device_id: i32 = switch.parse().unwrap_or(0);
+    let devices: Vec<(i32, Option<i32>)> = sqlx::query_as(
+        "SELECT DeviceID, ParentDevice FROM fac_Device \
+         WHERE Label = ? OR PrimaryIP = ? OR DeviceID = ?",
+    )
+    .bind(switch)
+    .bind(switch)
+    .bind(device_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let ids: Vec<i32> = devices
+        .iter()
+        .filter(|(_, parent)| {
+            !devices
+                .iter()
+                .any(|(device_id, _)| Some(*device_id) == *parent)
+        })
+        .map(|(device_id, _)| *device_id)
+        .collect();
+    match ids.as_slice() {
+        [] => Err(Error::not_found(switch)),
+        [id] => Ok(*id),
+        _ => Err(Error::Ambiguous {
+            query: switch.to_owned(),
+            count: ids.len(),
+            candidates: Vec::new(),
+        }),
+    }
+}
+
+/// Reads the port with the given label on the switch with the given label,
+/// IP address or `DeviceID`, including the ports of its stack members or
+/// line cards. Members with a port of the same label make it ambiguous.
+pub(crate) async fn fetch_switchport(
+    conn: &mut MySqlConnection,
+    switch: &str,
+    port: &str,
+) -> Result<PortRow, Error> {
+    let device_id = fetch_switch(conn, switch).await?;
+    let port = port.trim();
+    let mut rows = sqlx::query(&format!(
+        "{PORT_COLUMNS} WHERE (p.DeviceID = ? OR d.ParentDevice = ?) AND p.Label = ?"
+    ))
+    .bind(device_id)
+    .bind(device_id)
+    .bind(port)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let query = format!("{} {port}", switch.trim());
+    match rows.len() {
+        0 => Err(Error::not_found(query)),
+        1 => PortRow::try_from(rows.remove(0)),
+        count => Err(Error::Ambiguous {
+            query,
+            count,
+            candidates: Vec::new(),
+        }),
+    }
+}
+
+/// Reads all ports of the switch with the given label, IP address or
+/// `DeviceID`, ordered by port number. The ports of its stack members or
+/// line cards follow, ordered by slot.
+pub(crate) async fn fetch_switchports(
+    conn: &mut MySqlConnection,
+    switch: &str,
+) -> Result<Vec<PortRow>, Error> {
+    let device_id = fetch_switch(conn, switch).await?;
+    sqlx::query(&format!(
+        "{PORT_COLUMNS} WHERE p.DeviceID = ? OR d.ParentDevice = ? \
+         ORDER BY d.ParentDevice = ?, d.Position, p.PortNumber"
+    ))
+    .bind(device_id)
+    .bind(device_id)
+    .bind(device_id)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(PortRow::try_from)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_HOPS;
+
+    impl PortSource for Vec<PortRow> {
+        fn ports<'a>(
+            &'a mut self,
+            keys: &'a [(i32, i32)],
+            connected: bool,
+        ) -> BoxFuture<'a, Result<PortMap, Error>> {
+            let ports = self
+                .iter()
+                .filter_map(|row| {
+                    let key = match connected {
+                        false => row.key(),
+                        true => row.connected?,
+                    };
+                    keys.contains(&key).then(|| (key, row.clone()))
+                })
+                .collect();
+            Box::pin(async move { Ok(ports) })
+        }
+    }
+
+    fn port(device_type: &str, key: (i32, i32), connected: Option<(i32, i32)>) -> PortRow {
+        PortRow {
+            hop: Hop {
+                device_id: key.0,
+                device_label: format!("device-{}", key.0),
+                port_number: key.1,
+                port_label: key.1.to_string(),
+                port_notes: String::new(),
+                location: None,
+                media_type: None,
+                color: None,
+                chassis: None,
+            },
+            device_type: device_type.to_owned(),
+            primary_ip: String::new(),
+            connected,
+        }
+    }
+
+    fn panel(key: (i32, i32), connected: Option<(i32, i32)>) -> PortRow {
+        port("Patch Panel", key, connected)
+    }
+
+    fn keys(path: &[PortRow]) -> Vec<(i32, i32)> {
+        path.iter().map(PortRow::key).collect()
+    }
+
+    /// Wallsocket at panel 1, through panels 2 and 3 to port 5 of switch 10.
+    /// Port 3/1 only knows its cable from the other side.
+    fn three_panels() -> Vec<PortRow> {
+        vec![
+            panel((1, 1), None),
+            panel((1, -1), Some((2, -1))),
+            panel((2, -1), Some((1, -1))),
+            panel((2, 1), Some((3, 1))),
+            panel((3, 1), None),
+            panel((3, -1), Some((10, 5))),
+            port("Switch", (10, 5), Some((3, -1))),
+        ]
+    }
+
+    #[tokio::test]
+    async fn follows_several_panels() {
+        let mut rows = three_panels();
+        let start = rows[0].clone();
+        let paths = follow_many(&mut rows, vec![start], MAX_HOPS).await.unwrap();
+
+        let path = paths.into_iter().next().unwrap().unwrap();
+        assert_eq!(
+            keys(&path),
+            [(1, 1), (1, -1), (2, -1), (2, 1), (3, 1), (3, -1), (10, 5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn follows_several_panels_back() {
+        let mut rows = three_panels();
+        let start = rows[6].clone();
+        let paths = follow_back_many(&mut rows, vec![start], MAX_HOPS)
+            .await
+            .unwrap();
+
+        let path = paths.into_iter().next().unwrap().unwrap();
+
+        assert_eq!(
+            keys(&path),
+            [(1, 1), (1, -1), (2, -1), (2, 1), (3, 1), (3, -1), (10, 5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_the_cables_of_a_port_pair_apart() {
+        // Front and rear port 17 hold different cables, the switch port
+        // only knows its cable from the rear port.
+        let mut front = panel((1, 17), None);
+        front.hop.color = Some("yellow".to_owned());
+        let mut rear = panel((1, -17), Some((10, 5)));
+        rear.hop.color = Some("blue".to_owned());
+        rear.hop.media_type = Some("Cat6A".to_owned());
+        let mut rows = vec![front, rear, port("Switch", (10, 5), Some((1, -17)))];
+        let start = rows[0].clone();
+        let paths = follow_many(&mut rows, vec![start], MAX_HOPS).await.unwrap();
+
+        let cables: Vec<_> = paths[0]
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|row| row.hop.cable())
+            .collect();
+        assert_eq!(
+            cables,
+            [
+                Some("yellow".to_owned()),
+                Some("blue Cat6A".to_owned()),
+                Some("blue Cat6A".to_owned())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn follows_back_to_wallsockets_only() {
+        // Port 5 of switch 10 is patched through panels 1 and 2 to switch 20,
+        // port 6 is cabled to it directly.
+        let mut rows = vec![
+            port("Switch", (10, 5), Some((1, -1))),
+            panel((1, -1), Some((10, 5))),
+            panel((1, 1), Some((2, 1))),
+            panel((2, 1), Some((1, 1))),
+            panel((2, -1), Some((20, 1))),
+            port("Switch", (20, 1), Some((2, -1))),
+            port("Switch", (10, 6), Some((20, 2))),
+            port("Switch", (20, 2), Some((10, 6))),
+        ];
+
+        let starts = vec![rows[0].clone(), rows[6].clone()];
+        let paths = follow_back_many(&mut rows, starts, MAX_HOPS).await.unwrap();
+
+        assert_eq!(paths.len(), 2);
+        for path in paths {
+            assert!(matches!(path, Err(Error::NotFound { .. })));
+        }
+    }
+
+    #[tokio::test]
+    async fn detects_loops() {
+        let mut rows = vec![
+            panel((1, 1), None),
+            panel((1, -1), Some((2, -1))),
+            panel((2, -1), Some((1, -1))),
+            panel((2, 1), Some((1, 1))),
+        ];
+        let start = rows[0].clone();
+        let paths = follow_many(&mut rows, vec![start], MAX_HOPS).await.unwrap();
+
+        assert!(matches!(
+            &paths[0],
+            Err(Error::BrokenPath(message)) if message.starts_with("loop detected")
+        ));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_hops() {
+        // A chain of panels with more ports than MAX_HOPS, ending at a switch.
+        let panels = MAX_HOPS as i32;
+        let mut rows = vec![panel((1, 1), None)];
+        for device_id in 1..=panels {
+            let next = match device_id {
+                id if id == panels => (100, 1),
+                id => (id + 1, 1),
+            };
+            rows.push(panel((device_id, -1), Some(next)));
+            if device_id < panels {
+                rows.push(panel(next, Some((device_id, -1))));
+            }
+        }
+        rows.push(port("Switch", (100, 1), Some((panels, -1))));
+        let start = rows[0].clone();
+        let paths = follow_many(&mut rows, vec![start.clone()], MAX_HOPS)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            &paths[0],
+            Err(Error::BrokenPath(message)) if message.starts_with("gave up")
+        ));
+
+        let paths = follow_many(&mut rows, vec![start], 2 * MAX_HOPS + 1)
+            .await
+            .unwrap();
+        assert_eq!(paths[0].as_ref().unwrap().len(), 2 * MAX_HOPS + 1);
+    }
+}
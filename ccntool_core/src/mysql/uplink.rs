@@ -0,0 +1,274 @@
+use super::trace::{self, PortRow, PORT_COLUMNS};
+use crate::{trace::share_cable, Error, Uplink, MAX_HOPS, MAX_UPLINK_LEVELS};
+use sqlx::{MySqlConnection, Row};
+use std::collections::{HashMap, HashSet};
+
+/// Device types uplinks lead to. openDCIM has no type for routers and
+/// firewalls, they are usually set up as appliances.
+const NETWORK_TYPES: [&str; 2] = ["Switch", "Appliance"];
+
+/// Reads every uplink from one of `switches` or their stack members to
+/// another switch, ordered by switch and port. Links to the switches in
+/// `known` aren't followed, neither are stacking links between members of
+/// the same stack.
+async fn fetch_links(
+    conn: &mut MySqlConnection,
+    switches: &[i32],
+    known: &HashSet<i32>,
+) -> Result<Vec<Uplink>, Error> {
+    if switches.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let placeholders = vec!["?"; switches.len()].join(", ");
+    let sql = format!(
+        "{PORT_COLUMNS} WHERE (p.DeviceID IN ({placeholders}) \
+         OR d.ParentDevice IN ({placeholders})) AND p.ConnectedDeviceID > 0 \
+         ORDER BY p.DeviceID, p.PortNumber"
+    );
+    let mut query = sqlx::query(&sql);
+    for switch in switches.iter().chain(switches) {
+        query = query.bind(switch);
+    }
+    let ports = query
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(PortRow::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Follow every connection through its patch panels. Connections ending
+    // at a wallsocket or anything but a network device are no uplinks.
+    let keys: Vec<_> = ports.iter().filter_map(|port| port.connected).collect();
+    let peers = trace::fetch_ports(conn, &keys, false).await?;
+    let (ports, starts): (Vec<_>, Vec<_>) = ports
+        .into_iter()
+        .filter_map(|mut port| {
+            let mut peer = peers.get(&port.connected?)?.clone();
+            if !peer.is_patchpanel() && known.contains(&peer.hop.unit_id()) {
+                return None;
+            }
+            share_cable(&mut port.hop, &mut peer.hop);
+            Some((port, peer))
+        })
+        .unzip();
+    let paths = trace::follow_many(conn, starts, MAX_HOPS).await?;
+
+    let mut links = Vec::new();
+    for (port, path) in ports.into_iter().zip(paths) {
+        let Ok(mut path) = path else { continue };
+        let Some(end) = path.pop() else { continue };
+        let unit = end.hop.unit_id();
+        if !NETWORK_TYPES.contains(&end.device_type.as_str())
+            || unit == port.hop.unit_id()
+            || known.contains(&unit)
+        {
+            continue;
+        }
+        links.push(Uplink {
+            port: port.hop,
+            path: path.into_iter().map(|row| row.hop).collect(),
+            switch_ip: end.primary_ip.trim().parse().ok(),
+            switch_port: end.hop,
+        });
+    }
+
+    Ok(links)
+}
+
+/// Returns which of `switches` have a hostname matching `core`.
+async fn fetch_core(
+    conn: &mut MySqlConnection,
+    switches: &[i32],
+    core: &str,
+) -> Result<HashSet<i32>, Error> {
+    let sql = format!(
+        "SELECT DeviceID FROM fac_Device WHERE DeviceID IN ({}) AND Label REGEXP ?",
+        vec!["?"; switches.len()].join(", ")
+    );
+    let mut query = sqlx::query(&sql);
+    for switch in switches {
+        query = query.bind(switch);
+    }
+    let mut cores = HashSet::new();
+    for row in query.bind(core).fetch_all(&mut *conn).await? {
+        cores.insert(row.try_get("DeviceID")?);
+    }
+
+    Ok(cores)
+}
+
+/// Returns the `DeviceID` of the chassis or stack `switch` is a member of,
+/// or `switch` itself if it is standalone.
+async fn fetch_unit(conn: &mut MySqlConnection, switch: i32) -> Result<i32, Error> {
+    let row = sqlx::query("SELECT ParentDevice FROM fac_Device WHERE DeviceID = ?")
+        .bind(switch)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let parent = match row {
+        Some(row) => row.try_get::<Option<i32>, _>("ParentDevice")?,
+        None => None,
+    };
+
+    Ok(parent.filter(|&parent| parent > 0).unwrap_or(switch))
+}
+
+/// Follows the uplinks of the switch with `DeviceID` `switch` level by
+/// level, see [`DcimBackend::uplinks`](crate::DcimBackend::uplinks). Stacks
+/// and chassis count as one switch, whichever member the uplinks start or
+/// end at.
+pub(crate) async fn uplinks(
+    conn: &mut MySqlConnection,
+    switch: i32,
+    core: Option<&str>,
+) -> Result<Vec<Uplink>, Error> {
+    let switch = fetch_unit(conn, switch).await?;
+    // Every switch reached, with the uplinks leading to it from the level
+    // below.
+    let mut reached: HashMap<i32, Vec<Uplink>> = HashMap::new();
+    let mut known = HashSet::from([switch]);
+    let mut direct = Vec::new();
+    let mut frontier = vec![switch];
+
+    for level in 0..MAX_UPLINK_LEVELS {
+        let mut next = Vec::new();
+        for link in fetch_links(conn, &frontier, &known).await? {
+            let upper = link.switch_port.unit_id();
+            if level == 0 {
+                direct.push(link.clone());
+            }
+            if !next.contains(&upper) {
+                next.push(upper);
+            }
+            reached.entry(upper).or_default().push(link);
+        }
+
+        let Some(core) = core.filter(|_| !next.is_empty()) else {
+            break;
+        };
+
+        let cores = fetch_core(conn, &next, core).await?;
+        if let Some(&top) = next.iter().find(|upper| cores.contains(upper)) {
+            return Ok(chain(&reached, switch, top));
+        }
+        known.extend(&next);
+        frontier = next;
+    }
+
+    // No core switch reached, at least tell which switches serve this one.
+    // There is no telling which way a link leads, but switches connected to
+    // nothing else hang below this one.
+    let mut neighbours: Vec<i32> = direct
+        .iter()
+        .map(|link| link.switch_port.unit_id())
+        .collect();
+    neighbours.sort_unstable();
+    neighbours.dedup();
+    let above: HashSet<i32> = fetch_links(conn, &neighbours, &HashSet::from([switch]))
+        .await?
+        .iter()
+        .map(|link| link.port.unit_id())
+        .collect();
+    direct.retain(|link| above.contains(&link.switch_port.unit_id()));
+
+    Ok(direct)
+}
+
+/// Walks back from the switch `top` to `switch`, returning the uplinks in
+/// between from the bottom up. Parallel uplinks between the same switches
+/// are all included.
+fn chain(reached: &HashMap<i32, Vec<Uplink>>, switch: i32, top: i32) -> Vec<Uplink> {
+    let mut chain = Vec::new();
+    let mut upper = top;
+    while upper != switch {
+        let Some(links) = reached.get(&upper) else {
+            break;
+        };
+        let lower = links[0].port.unit_id();
+        chain.extend(
+            links
+                .iter()
+                .rev()
+                .filter(|link| link.port.unit_id() == lower)
+                .cloned(),
+        );
+        upper = lower;
+    }
+    chain.reverse();
+
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hop;
+
+    fn hop(device_id: i32, port_number: i32) -> Hop {
+        Hop {
+            device_id,
+            device_label: format!("sw-{device_id}"),
+            port_number,
+            port_label: port_number.to_string(),
+            port_notes: String::new(),
+            location: None,
+            media_type: None,
+            color: None,
+            chassis: None,
+        }
+    }
+
+    /// An uplink from port `port` of switch `lower` to switch `upper`.
+    fn link(lower: i32, upper: i32, port: i32) -> Uplink {
+        Uplink {
+            port: hop(lower, port),
+            path: Vec::new(),
+            switch_port: hop(upper, port),
+            switch_ip: None,
+        }
+    }
+
+    fn ends(chain: &[Uplink]) -> Vec<(i32, i32, i32)> {
+        chain
+            .iter()
+            .map(|link| {
+                let (port, upper) = (&link.port, &link.switch_port);
+                (port.device_id, upper.device_id, port.port_number)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chain_runs_from_the_bottom_up() {
+        let reached = HashMap::from([(2, vec![link(1, 2, 49)]), (3, vec![link(2, 3, 1)])]);
+
+        assert_eq!(ends(&chain(&reached, 1, 3)), [(1, 2, 49), (2, 3, 1)]);
+        assert_eq!(ends(&chain(&reached, 1, 2)), [(1, 2, 49)]);
+        assert!(chain(&reached, 1, 1).is_empty());
+    }
+
+    #[test]
+    fn chain_keeps_parallel_uplinks() {
+        let reached = HashMap::from([
+            (2, vec![link(1, 2, 49), link(1, 2, 50)]),
+            (3, vec![link(2, 3, 1), link(2, 3, 2)]),
+        ]);
+
+        assert_eq!(
+            ends(&chain(&reached, 1, 3)),
+            [(1, 2, 49), (1, 2, 50), (2, 3, 1), (2, 3, 2)]
+        );
+    }
+
+    #[test]
+    fn chain_follows_the_first_switch_below() {
+        // Switch 4 is reached from both 2 and 3, the chain goes through 2.
+        let reached = HashMap::from([
+            (2, vec![link(1, 2, 49)]),
+            (3, vec![link(1, 3, 50)]),
+            (4, vec![link(2, 4, 1), link(3, 4, 2)]),
+        ]);
+
+        assert_eq!(ends(&chain(&reached, 1, 4)), [(1, 2, 49), (2, 4, 1)]);
+    }
+}
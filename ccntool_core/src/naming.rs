@@ -0,0 +1,257 @@
+//! Naming schemes telling valid port identifiers apart from everything else
+//! stored in `fac_Ports`.
+//!
+//! A naming scheme is a MySQL regular expression, the `fac_Ports` column it
+//! is matched against and the [`PortKind`] of the matching ports.
+//! Without configuration the schemes used at HSD apply, see
+//! [`NamingScheme::hsd`]. Other conventions are set in the config file,
+//! replacing the defaults as a whole:
+//!
+//! ```toml
+//! [[naming]]
+//! pattern = '^[A-Z][0-9]{2}-[0-9]{3}/[0-9]+$'
+//! column = "label"
+//! kind = "wallsocket"
+//!
+//! [[naming]]
+//! pattern = '^AP-'
+//! kind = "access-point"
+//! ```
+
+use crate::SocketId;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt};
+
+/// What is plugged into a port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PortKind {
+    /// An ordinary wallsocket in a room.
+    Wallsocket,
+    /// A wireless access point.
+    AccessPoint,
+    /// Media technology like projectors or lecture hall equipment.
+    MediaTechnology,
+}
+
+impl PortKind {
+    /// All kinds, in display order.
+    pub const ALL: [PortKind; 3] = [
+        PortKind::Wallsocket,
+        PortKind::AccessPoint,
+        PortKind::MediaTechnology,
+    ];
+
+    /// A short hint on what to expect from a port of this kind, if there is
+    /// anything special about it.
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            PortKind::Wallsocket => None,
+            PortKind::AccessPoint => Some("access point, expect PoE"),
+            PortKind::MediaTechnology => Some("media technology, not a regular workstation port"),
+        }
+    }
+}
+
+impl fmt::Display for PortKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            PortKind::Wallsocket => "wallsocket",
+            PortKind::AccessPoint => "access point",
+            PortKind::MediaTechnology => "media technology",
+        })
+    }
+}
+
+/// The `fac_Ports` column holding the identifier of a port.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdColumn {
+    /// The port description (`Notes` field), as used at HSD.
+    #[default]
+    Notes,
+    /// The port name (`Label` field).
+    Label,
+}
+
+impl IdColumn {
+    /// Name of the column in the `fac_Ports` table.
+    pub fn sql(self) -> &'static str {
+        match self {
+            IdColumn::Notes => "Notes",
+            IdColumn::Label => "Label",
+        }
+    }
+}
+
+/// A pattern for valid port identifiers.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NamingScheme {
+    /// MySQL regular expression matching the identifiers.
+    pub pattern: String,
+    /// Column the identifiers are stored in.
+    #[serde(default)]
+    pub column: IdColumn,
+    /// What the matching ports are.
+    pub kind: PortKind,
+}
+
+impl NamingScheme {
+    /// Creates a naming scheme matching `pattern` against the `Notes` field.
+    pub fn new(pattern: &str, kind: PortKind) -> Self {
+        NamingScheme {
+            pattern: pattern.to_owned(),
+            column: IdColumn::Notes,
+            kind,
+        }
+    }
+
+    /// The naming schemes used at HSD: wallsockets like `01.1.001-1`, media
+    /// technology starting with `MT-` and access points containing `APD`,
+    /// `APP`, `APR`, `APM` or `APK`, all stored in the `Notes` field.
+    pub fn hsd() -> Vec<Self> {
+        vec![
+            NamingScheme::new(
+                "^[0-9]+.[EU0-9]+.[0-9]+-[0-9a-z/,]+?$",
+                PortKind::Wallsocket,
+            ),
+            NamingScheme::new("^MT-", PortKind::MediaTechnology),
+            NamingScheme::new("APD|APP|APR|APM|APK", PortKind::AccessPoint),
+        ]
+    }
+}
+
+/// A valid port identifier, as returned by
+/// [`DcimBackend::identifiers`](crate::DcimBackend::identifiers).
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Identifier {
+    /// The identifier, e.g. `01.1.001-1`.
+    pub name: String,
+    /// Kind of the first naming scheme the identifier matches.
+    pub kind: PortKind,
+    /// Description of the physical port if it is shared with other outlets,
+    /// e.g. `01.1.001-1,2` for `01.1.001-2`.
+    #[serde(default)]
+    pub shared: Option<String>,
+}
+
+/// Returns the distinct columns the identifiers of `schemes` are stored in.
+pub(crate) fn columns(schemes: &[NamingScheme]) -> Vec<IdColumn> {
+    let mut seen = HashSet::new();
+    schemes
+        .iter()
+        .map(|scheme| scheme.column)
+        .filter(|column| seen.insert(*column))
+        .collect()
+}
+
+/// Turns port identifiers and their kind into the sorted list of
+/// [`Identifier`]s. The first kind given for an identifier wins.
+pub(crate) fn collect(matches: impl IntoIterator<Item = (String, PortKind)>) -> Vec<Identifier> {
+    let mut seen = HashSet::new();
+    let mut identifiers = Vec::new();
+    for (name, kind) in matches {
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+
+        // Combined descriptions are listed once per outlet.
+        match name.parse::<SocketId>() {
+            Ok(socket) if socket.is_combined() => {
+                identifiers.extend(socket.outlets().into_iter().map(|outlet| Identifier {
+                    name: outlet.to_string(),
+                    kind,
+                    shared: Some(name.clone()),
+                }));
+            }
+            _ => identifiers.push(Identifier {
+                name,
+                kind,
+                shared: None,
+            }),
+        }
+    }
+
+    // `None` sorts before `Some`, so put the identifiers that parse first.
+    // Outlets with a port of their own win over shared ones.
+    identifiers.sort_by_cached_key(|id| {
+        let socket = id.name.parse::<SocketId>().ok();
+        (
+            socket.is_none(),
+            socket,
+            id.name.clone(),
+            id.shared.is_some(),
+        )
+    });
+    identifiers.dedup_by(|a, b| a.name == b.name);
+
+    identifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_names(matches: &[(&str, PortKind)]) -> Vec<Identifier> {
+        collect(matches.iter().map(|(name, kind)| (name.to_string(), *kind)))
+    }
+
+    fn shared(identifiers: &[Identifier]) -> Vec<(&str, Option<&str>)> {
+        identifiers
+            .iter()
+            .map(|id| (id.name.as_str(), id.shared.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn combined_names_are_expanded_next_to_singles() {
+        let identifiers = collect_names(&[
+            ("01.1.001-1,2", PortKind::Wallsocket),
+            ("01.1.001-3", PortKind::Wallsocket),
+        ]);
+
+        assert_eq!(
+            shared(&identifiers),
+            [
+                ("01.1.001-1", Some("01.1.001-1,2")),
+                ("01.1.001-2", Some("01.1.001-1,2")),
+                ("01.1.001-3", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn own_ports_win_over_shared_ones() {
+        // The shared port is seen first, but the outlet has one of its own.
+        let identifiers = collect_names(&[
+            ("01.1.001-1/2", PortKind::Wallsocket),
+            ("01.1.001-2", PortKind::Wallsocket),
+        ]);
+
+        assert_eq!(
+            shared(&identifiers),
+            [("01.1.001-1", Some("01.1.001-1/2")), ("01.1.001-2", None),]
+        );
+    }
+
+    #[test]
+    fn names_seen_twice_keep_the_first_kind() {
+        let identifiers = collect_names(&[
+            ("MT-APD-1", PortKind::MediaTechnology),
+            ("01.1.001-1", PortKind::Wallsocket),
+            ("MT-APD-1", PortKind::AccessPoint),
+        ]);
+
+        let kinds: Vec<_> = identifiers
+            .iter()
+            .map(|id| (id.name.as_str(), id.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("01.1.001-1", PortKind::Wallsocket),
+                ("MT-APD-1", PortKind::MediaTechnology),
+            ]
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Ranked fuzzy search over port identifiers, switch labels and switch IP
+//! addresses, meant for autocompletion in the frontends.
+//!
+//! A [`SearchIndex`] is loaded once, see
+//! [`DcimBackend::search_index`](crate::DcimBackend::search_index), and
+//! then searched in memory on every keystroke.
+
+use crate::{suggest, Identifier, PortKind, RoomId, SocketId};
+use serde::{Deserialize, Serialize};
+
+/// What a [`SearchEntry`] names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchKind {
+    /// A port identifier like a wallsocket, see [`Identifier`].
+    Port(PortKind),
+    /// A switch hostname (`Label` field from `fac_Device` table).
+    Switch,
+    /// A switch IP address (`PrimaryIP` field from `fac_Device` table).
+    SwitchIp,
+}
+
+/// Something that can be searched for.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SearchEntry {
+    /// The text to complete to, e.g. `01.1.001-1` or `sw-h1-e2`.
+    pub text: String,
+    /// What the text names.
+    pub kind: SearchKind,
+    /// Additional information to show next to the text: the shared port
+    /// description of an outlet, the IP address of a switch or the hostname
+    /// of a switch IP.
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+/// All port identifiers, switch labels and switch IPs, ready to be searched.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use ccntool_core::*;
+///
+/// # async fn run() -> Result<(), Error> {
+/// let backend = backend::connect(Config::load()?).await?;
+/// let index = backend.search_index().await?;
+/// for entry in index.search("1.1.1-").into_iter().take(10) {
+///     println!("{}", entry.text);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SearchIndex {
+    entries: Vec<SearchEntry>,
+}
+
+/// How well an entry matches a query, best first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Quality {
+    /// The whole entry is the query.
+    Exact,
+    /// The entry starts with the query, shorter entries first.
+    Prefix(usize),
+    /// Every word of the query starts a word of the entry, so `h1 e2`
+    /// finds `sw-h1-e2`.
+    Words,
+    /// The entry contains the query somewhere.
+    Substring,
+    /// The query is a few typos away from the entry or its beginning.
+    Typo(usize),
+}
+
+impl SearchIndex {
+    /// Creates an index over the given entries. Entries of equal quality
+    /// are returned in the order given here.
+    pub fn new(entries: Vec<SearchEntry>) -> Self {
+        SearchIndex { entries }
+    }
+
+    /// Every entry of the index.
+    pub fn entries(&self) -> &[SearchEntry] {
+        &self.entries
+    }
+
+    /// Returns every entry matching `query`, best match first.
+    ///
+    /// Matching ignores case. Exact matches come first, followed by entries
+    /// starting with the query, entries with a word starting with each word
+    /// of the query, entries containing the query and finally entries a few
+    /// typos away. Wallsocket and room descriptions are also tried in their
+    /// normalised form, so `1.1.1` completes to `01.1.001-1` while `10.1.2`
+    /// still finds the switch IP `10.1.2.5`.
+    pub fn search(&self, query: &str) -> Vec<&SearchEntry> {
+        let raw = query.trim().to_lowercase();
+        if raw.is_empty() {
+            return Vec::new();
+        }
+        let mut queries = vec![raw];
+        let normalised = normalise(query);
+        if normalised != queries[0] {
+            queries.push(normalised);
+        }
+        let queries: Vec<(&str, Vec<&str>)> = queries
+            .iter()
+            .map(|query| (query.as_str(), words(query)))
+            .collect();
+
+        let mut hits: Vec<(Quality, &SearchEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let best = queries
+                    .iter()
+                    .filter_map(|(query, words)| quality(query, words, &entry.text))
+                    .min()?;
+                Some((best, entry))
+            })
+            .collect();
+        hits.sort_by_key(|(quality, _)| *quality);
+
+        hits.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+/// Lowercases the query, using the canonical form of wallsocket and room
+/// descriptions, including a room about to be followed by an outlet like
+/// `1.1.1-`.
+fn normalise(query: &str) -> String {
+    let query = query.trim();
+    let room = |room: &str| room.parse::<RoomId>().ok();
+    let canonical = match query.parse::<SocketId>() {
+        Ok(socket) => socket.to_string(),
+        Err(_) => match (room(query), query.strip_suffix('-').and_then(room)) {
+            (Some(room), _) => room.to_string(),
+            (None, Some(room)) => format!("{room}-"),
+            (None, None) => query.to_owned(),
+        },
+    };
+    canonical.to_lowercase()
+}
+
+/// Splits at everything but letters and digits.
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Rates how well `text` matches the normalised `query`, `None` if it
+/// doesn't match at all.
+fn quality(query: &str, query_words: &[&str], text: &str) -> Option<Quality> {
+    let text = text.to_lowercase();
+    if text == query {
+        return Some(Quality::Exact);
+    }
+    if text.starts_with(query) {
+        return Some(Quality::Prefix(text.len() - query.len()));
+    }
+    let text_words = words(&text);
+    let starts_word =
+        |query_word: &&str| text_words.iter().any(|word| word.starts_with(query_word));
+    if !query_words.is_empty() && query_words.iter().all(starts_word) {
+        return Some(Quality::Words);
+    }
+    if text.contains(query) {
+        return Some(Quality::Substring);
+    }
+
+    // Very short queries would be a typo away from almost everything.
+    let length = query.chars().count();
+    if length < 3 {
+        return None;
+    }
+
+    // Compare against the beginning of the entry as well, so typos are
+    // forgiven while the query is still being typed. Allow fewer typos than
+    // for "did you mean" suggestions, as every keystroke is searched.
+    let limit = (length / 4).max(1);
+    let beginning: String = text.chars().take(length).collect();
+    let distance = suggest::levenshtein(query, &text).min(suggest::levenshtein(query, &beginning));
+    (distance <= limit).then_some(Quality::Typo(distance))
+}
+
+/// Builds the index from the port identifiers, followed by the switches as
+/// hostname and IP address, which may be empty, and finally their IP
+/// addresses.
+pub(crate) fn build(identifiers: Vec<Identifier>, switches: Vec<(String, String)>) -> SearchIndex {
+    let mut entries: Vec<SearchEntry> = identifiers
+        .into_iter()
+        .map(|Identifier { name, kind, shared }| SearchEntry {
+            text: name,
+            kind: SearchKind::Port(kind),
+            detail: shared,
+        })
+        .collect();
+
+    let mut ips = Vec::new();
+    for (label, ip) in switches {
+        let ip = ip.trim().to_owned();
+        if !ip.is_empty() {
+            ips.push(SearchEntry {
+                text: ip.clone(),
+                kind: SearchKind::SwitchIp,
+                detail: Some(label.clone()),
+            });
+        }
+        entries.push(SearchEntry {
+            text: label,
+            kind: SearchKind::Switch,
+            detail: (!ip.is_empty()).then_some(ip),
+        });
+    }
+    entries.extend(ips);
+
+    SearchIndex::new(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str, kind: SearchKind) -> SearchEntry {
+        SearchEntry {
+            text: text.to_owned(),
+            kind,
+            detail: None,
+        }
+    }
+
+    fn index() -> SearchIndex {
+        SearchIndex::new(vec![
+            entry("01.1.001-1", SearchKind::Port(PortKind::Wallsocket)),
+            entry("01.1.001-10", SearchKind::Port(PortKind::Wallsocket)),
+            entry("01.1.002-1", SearchKind::Port(PortKind::Wallsocket)),
+            entry("sw-h1-e2", SearchKind::Switch),
+            entry("sw-h2-e1", SearchKind::Switch),
+            entry("10.1.2.5", SearchKind::SwitchIp),
+        ])
+    }
+
+    fn texts<'a>(index: &'a SearchIndex, query: &str) -> Vec<&'a str> {
+        index
+            .search(query)
+            .into_iter()
+            .map(|entry| entry.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn empty_query_finds_nothing() {
+        assert!(index().search("  ").is_empty());
+    }
+
+    #[test]
+    fn exact_match_comes_first() {
+        assert_eq!(
+            texts(&index(), "01.1.001-1")[..2],
+            ["01.1.001-1", "01.1.001-10"]
+        );
+    }
+
+    #[test]
+    fn sloppy_wallsocket_is_normalised() {
+        assert_eq!(texts(&index(), "1.1.1-1")[0], "01.1.001-1");
+        assert_eq!(
+            texts(&index(), "1.1.1-")[..2],
+            ["01.1.001-1", "01.1.001-10"]
+        );
+    }
+
+    #[test]
+    fn ip_prefix_is_not_taken_for_a_room() {
+        assert_eq!(texts(&index(), "10.1.2")[0], "10.1.2.5");
+    }
+
+    #[test]
+    fn words_match_anywhere() {
+        assert_eq!(texts(&index(), "h1 e2"), ["sw-h1-e2"]);
+    }
+
+    #[test]
+    fn prefix_beats_substring() {
+        let index = SearchIndex::new(vec![
+            entry("dsw-h1", SearchKind::Switch),
+            entry("sw-h1-e2", SearchKind::Switch),
+        ]);
+        assert_eq!(texts(&index, "sw"), ["sw-h1-e2", "dsw-h1"]);
+    }
+
+    #[test]
+    fn typos_are_forgiven_within_limits() {
+        assert_eq!(texts(&index(), "sw-h1-e3")[0], "sw-h1-e2");
+        assert!(texts(&index(), "xy").is_empty());
+        assert!(texts(&index(), "core-9").is_empty());
+    }
+}
@@ -0,0 +1,427 @@
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// A wallsocket identifier like `01.1.001-1`, made up of building, floor,
+/// room and outlet.
+///
+/// The floor is either a number, `E` for the ground floor or `U` (optionally
+/// followed by a number) for basement levels. The outlet is a number or
+/// letter, possibly naming several outlets like `1,2` or `1/2`.
+///
+/// Parsing is lenient about whitespace, leading zeros and case, while
+/// [`Display`](fmt::Display) always writes the canonical form.
+///
+/// Identifiers order by building, floor level, room and outlet, so that
+/// `01.U.001-1` comes before `01.E.001-1` and `01.1.002-2` before
+/// `01.1.010-1`.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccntool_core::SocketId;
+///
+/// let socket: SocketId = "01.1.001-1".parse().unwrap();
+/// assert_eq!(socket.building(), 1);
+/// assert_eq!(socket.floor(), "1");
+/// assert_eq!(socket.room(), 1);
+/// assert_eq!(socket.outlet(), "1");
+/// assert_eq!(socket.to_string(), "01.1.001-1");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SocketId {
+    room_id: RoomId,
+    outlet: String,
+}
+
+/// A room identifier like `01.1.001`, the part of a [`SocketId`] before the
+/// outlet.
+///
+/// Rooms order by building, floor level and room number, and parse as
+/// leniently as socket identifiers.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccntool_core::{RoomId, SocketId};
+///
+/// let room: RoomId = "1.01.1".parse().unwrap();
+/// let socket: SocketId = "01.1.001-1".parse().unwrap();
+/// assert_eq!(room.to_string(), "01.1.001");
+/// assert_eq!(socket.room_id(), &room);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RoomId {
+    building: u32,
+    floor: String,
+    room: u32,
+}
+
+impl RoomId {
+    /// Building number, e.g. `1` for `01.1.001`.
+    pub fn building(&self) -> u32 {
+        self.building
+    }
+
+    /// Floor as written in the identifier, e.g. `1`, `E` or `U2`.
+    pub fn floor(&self) -> &str {
+        &self.floor
+    }
+
+    /// Floor as a level relative to the ground floor, negative for basement
+    /// levels.
+    pub fn floor_level(&self) -> i32 {
+        floor_level(&self.floor).unwrap_or_default()
+    }
+
+    /// Room number on the floor, e.g. `1` for `01.1.001`.
+    pub fn room(&self) -> u32 {
+        self.room
+    }
+}
+
+impl SocketId {
+    /// Building number, e.g. `1` for `01.1.001-1`.
+    pub fn building(&self) -> u32 {
+        self.room_id.building
+    }
+
+    /// Floor as written in the identifier, e.g. `1`, `E` or `U2`.
+    pub fn floor(&self) -> &str {
+        &self.room_id.floor
+    }
+
+    /// Floor as a level relative to the ground floor, negative for basement
+    /// levels.
+    pub fn floor_level(&self) -> i32 {
+        self.room_id.floor_level()
+    }
+
+    /// Room number on the floor, e.g. `1` for `01.1.001-1`.
+    pub fn room(&self) -> u32 {
+        self.room_id.room
+    }
+
+    /// Outlet in the room, e.g. `1` or `1,2`.
+    pub fn outlet(&self) -> &str {
+        &self.outlet
+    }
+
+    /// The room the wallsocket is in.
+    pub fn room_id(&self) -> &RoomId {
+        &self.room_id
+    }
+
+    /// Building, floor and room without the outlet, e.g. `01.1.001`.
+    pub fn location(&self) -> String {
+        self.room_id.to_string()
+    }
+
+    /// Whether the identifier names several outlets sharing one physical
+    /// port, like `01.1.001-1,2` or `01.1.001-1/2`.
+    pub fn is_combined(&self) -> bool {
+        self.outlet.contains([',', '/'])
+    }
+
+    /// Splits a combined identifier into one identifier per outlet, so
+    /// `01.1.001-1,2` yields `01.1.001-1` and `01.1.001-2`. Identifiers
+    /// naming a single outlet yield themselves.
+    pub fn outlets(&self) -> Vec<SocketId> {
+        self.outlet
+            .split([',', '/'])
+            .filter(|outlet| !outlet.is_empty())
+            .map(|outlet| SocketId {
+                outlet: outlet.to_owned(),
+                ..self.clone()
+            })
+            .collect()
+    }
+}
+
+/// Parses a floor into a level: `E` is the ground floor, `U` and `U<n>` are
+/// basement levels and plain numbers are upper floors. There is no `U0`.
+fn floor_level(floor: &str) -> Option<i32> {
+    let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    match floor {
+        "E" => Some(0),
+        "U" => Some(-1),
+        _ => match floor.strip_prefix('U') {
+            Some(level) if all_digits(level) => level
+                .parse::<i32>()
+                .ok()
+                .filter(|&level| level > 0)
+                .map(|level| -level),
+            Some(_) => None,
+            None if all_digits(floor) => floor.parse().ok(),
+            None => None,
+        },
+    }
+}
+
+/// Splits an outlet into its leading number and the rest, so `2` sorts
+/// before `10`.
+fn outlet_key(outlet: &str) -> (u32, &str) {
+    let digits = outlet
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(outlet.len());
+    (
+        outlet[..digits].parse().unwrap_or(u32::MAX),
+        &outlet[digits..],
+    )
+}
+
+/// Writes a floor the canonical way, e.g. `01` as `1` and `u02` as `U2`.
+fn canonical_floor(floor: &str) -> Option<String> {
+    let floor = floor.to_ascii_uppercase();
+    let level = floor_level(&floor)?;
+    match floor.as_str() {
+        "E" | "U" => Some(floor),
+        _ if level < 0 => Some(format!("U{}", -level)),
+        _ => Some(level.to_string()),
+    }
+}
+
+impl FromStr for RoomId {
+    type Err = Error;
+
+    /// Parses a room identifier, tolerating whitespace, leading zeros and
+    /// the case of the floor, so `1.1.1` and `01.01.001` parse as
+    /// `01.1.001`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidSocket(s.to_owned());
+        let input: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut parts = input.split('.');
+        let (Some(building), Some(floor), Some(room), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !all_digits(building) || !all_digits(room) {
+            return Err(invalid());
+        }
+
+        Ok(RoomId {
+            building: building.parse().map_err(|_| invalid())?,
+            floor: canonical_floor(floor).ok_or_else(invalid)?,
+            room: room.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl FromStr for SocketId {
+    type Err = Error;
+
+    /// Parses an identifier, tolerating whitespace, missing or extra leading
+    /// zeros and the case of floor and outlet, so `1.1.1-1`,
+    /// `01.1.001 - 1` and `01.01.001-1` all parse as `01.1.001-1`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidSocket(s.to_owned());
+        let input: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let (location, outlet) = input.split_once('-').ok_or_else(invalid)?;
+        let outlet = outlet.to_ascii_lowercase();
+        let valid_outlet = !outlet.is_empty()
+            && outlet
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_ascii_lowercase() || c == '/' || c == ',');
+        if !valid_outlet {
+            return Err(invalid());
+        }
+
+        Ok(SocketId {
+            room_id: location.parse().map_err(|_| invalid())?,
+            outlet,
+        })
+    }
+}
+
+impl TryFrom<String> for RoomId {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for SocketId {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Error> {
+        value.parse()
+    }
+}
+
+impl From<RoomId> for String {
+    fn from(room: RoomId) -> Self {
+        room.to_string()
+    }
+}
+
+impl From<SocketId> for String {
+    fn from(socket: SocketId) -> Self {
+        socket.to_string()
+    }
+}
+
+impl fmt::Display for RoomId {
+    /// Formats the identifier with a two digit building and three digit
+    /// room number.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}.{}.{:03}", self.building, self.floor, self.room)
+    }
+}
+
+impl fmt::Display for SocketId {
+    /// Formats the identifier the way it is written on the wallsocket, with
+    /// a two digit building and three digit room number.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.room_id, self.outlet)
+    }
+}
+
+impl Ord for RoomId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.building
+            .cmp(&other.building)
+            .then_with(|| self.floor_level().cmp(&other.floor_level()))
+            .then_with(|| self.floor.cmp(&other.floor))
+            .then_with(|| self.room.cmp(&other.room))
+    }
+}
+
+impl PartialOrd for RoomId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SocketId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.room_id
+            .cmp(&other.room_id)
+            .then_with(|| outlet_key(&self.outlet).cmp(&outlet_key(&other.outlet)))
+            .then_with(|| self.outlet.cmp(&other.outlet))
+    }
+}
+
+impl PartialOrd for SocketId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn socket(s: &str) -> SocketId {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_leniently_into_canonical_form() {
+        for input in [
+            "01.1.001-1",
+            "1.1.1-1",
+            "01.01.001-1",
+            " 01.1.001 - 1 ",
+            "001.1.1-1",
+        ] {
+            assert_eq!(socket(input).to_string(), "01.1.001-1", "{input}");
+        }
+        assert_eq!(socket("1.e.1-a").to_string(), "01.E.001-a");
+        assert_eq!(socket("1.u02.1-1").to_string(), "01.U2.001-1");
+        assert_eq!(socket("1.U.1-1,2").to_string(), "01.U.001-1,2");
+    }
+
+    #[test]
+    fn rejects_invalid_identifiers() {
+        for input in [
+            "",
+            "01.1.001",
+            "01.1-1",
+            "01.1.001.1-1",
+            "0a.1.001-1",
+            "01.1.00x-1",
+            "01.1.001-",
+            "01.1.001-1+2",
+            "01.X.001-1",
+            "01.U0.001-1",
+            "01.Ux.001-1",
+            "01.-1.001-1",
+        ] {
+            assert!(input.parse::<SocketId>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn exposes_parts() {
+        let socket = socket("3.U2.17-4");
+        assert_eq!(socket.building(), 3);
+        assert_eq!(socket.floor(), "U2");
+        assert_eq!(socket.floor_level(), -2);
+        assert_eq!(socket.room(), 17);
+        assert_eq!(socket.outlet(), "4");
+        assert_eq!(socket.location(), "03.U2.017");
+        assert_eq!(socket.room_id(), &"3.u2.17".parse::<RoomId>().unwrap());
+    }
+
+    #[test]
+    fn orders_by_floor_level() {
+        let mut sockets = [
+            socket("01.2.001-1"),
+            socket("01.E.001-1"),
+            socket("01.1.001-1"),
+            socket("01.U.001-1"),
+            socket("01.U2.001-1"),
+        ];
+        sockets.sort();
+        let sorted: Vec<String> = sockets.iter().map(SocketId::to_string).collect();
+        assert_eq!(
+            sorted,
+            [
+                "01.U2.001-1",
+                "01.U.001-1",
+                "01.E.001-1",
+                "01.1.001-1",
+                "01.2.001-1"
+            ]
+        );
+    }
+
+    #[test]
+    fn orders_rooms_and_outlets_numerically() {
+        assert!(socket("01.1.002-2") < socket("01.1.010-1"));
+        assert!(socket("01.1.001-2") < socket("01.1.001-10"));
+        assert!(socket("01.1.001-10") < socket("01.1.001-a"));
+        assert!(socket("01.9.999-9") < socket("02.U.001-1"));
+    }
+
+    #[test]
+    fn expands_combined_outlets() {
+        for input in ["01.1.001-1,2", "01.1.001-1/2"] {
+            let combined = socket(input);
+            assert!(combined.is_combined());
+            assert_eq!(
+                combined.outlets(),
+                [socket("01.1.001-1"), socket("01.1.001-2")]
+            );
+        }
+
+        let single = socket("01.1.001-1");
+        assert!(!single.is_combined());
+        assert_eq!(single.outlets(), std::slice::from_ref(&single));
+        assert_eq!(socket("01.1.001-1,,3").outlets().len(), 2);
+    }
+
+    #[test]
+    fn parses_rooms() {
+        let room: RoomId = "1.01.1".parse().unwrap();
+        assert_eq!(room.to_string(), "01.1.001");
+        assert!("01.1.001-1".parse::<RoomId>().is_err());
+        assert!("01.1".parse::<RoomId>().is_err());
+    }
+}
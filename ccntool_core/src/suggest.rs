@@ -0,0 +1,98 @@
+//! "Did you mean" suggestions for descriptions that don't exist.
+
+/// Maximum number of suggestions returned.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Returns the `candidates` closest to `query` by edit distance, ignoring
+/// case. Candidates differing in more than a third of the characters are
+/// left out.
+pub(crate) fn closest<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let query = query.to_lowercase();
+    let limit = (query.chars().count() / 3).max(1);
+
+    let mut scored: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (levenshtein(&query, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_owned())
+        .collect()
+}
+
+/// Number of single character insertions, deletions and substitutions
+/// needed to turn `a` into `b`.
+pub(crate) fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("01.1.001-1", "01.1.001-2"), 1);
+        assert_eq!(levenshtein("01.1.001-1", "01.1.001-10"), 1);
+    }
+
+    #[test]
+    fn closest_orders_by_distance() {
+        let candidates = ["01.1.001-12", "01.1.001-2", "01.1.001-1", "01.1.002-1"];
+        assert_eq!(
+            closest("01.1.001-1", candidates),
+            ["01.1.001-1", "01.1.001-12", "01.1.001-2", "01.1.002-1"]
+        );
+    }
+
+    #[test]
+    fn closest_ignores_case() {
+        assert_eq!(
+            closest("apd-01", ["APD-01", "APP-01"]),
+            ["APD-01", "APP-01"]
+        );
+    }
+
+    #[test]
+    fn closest_leaves_out_distant_candidates() {
+        // 10 characters allow 3 edits, short queries still allow 1.
+        assert_eq!(
+            closest("01.1.001-1", ["01.1.001-1234", "01.1.001-12345"]),
+            ["01.1.001-1234"]
+        );
+        assert_eq!(closest("ab", ["ac", "cd"]), ["ac"]);
+        assert!(closest("sw-h1-e2", ["core-1"]).is_empty());
+    }
+
+    #[test]
+    fn closest_returns_few_distinct_suggestions() {
+        let candidates = ["a1", "a2", "a3", "a4", "a5", "a6", "a1"];
+        let suggestions = closest("a0", candidates);
+        assert_eq!(suggestions, ["a1", "a2", "a3", "a4", "a5"]);
+    }
+}
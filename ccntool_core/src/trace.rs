@@ -0,0 +1,273 @@
+use crate::{naming::IdColumn, SocketId};
+use serde::{Deserialize, Serialize};
+use std::{fmt, net::IpAddr};
+
+/// Maximum number of ports a lookup passes before giving up. Each
+/// patch panel on the way takes two, its front and its rear port.
+pub const MAX_HOPS: usize = 32;
+
+/// A single port along the path from a wallsocket to its switch.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Hop {
+    /// Device ID of the patch panel or switch (`DeviceID` field from
+    /// `fac_Device` table)
+    pub device_id: i32,
+    /// Name of the patch panel or switch (`Label` field from `fac_Device`
+    /// table)
+    pub device_label: String,
+    /// Port number on the device (`PortNumber` field from `fac_Ports` table)
+    pub port_number: i32,
+    /// Port name on the device (`Label` field from `fac_Ports` table)
+    pub port_label: String,
+    /// Port description (`Notes` field from `fac_Ports` table)
+    pub port_notes: String,
+    /// Where the device is mounted, `None` if it isn't assigned to a
+    /// cabinet
+    #[serde(default)]
+    pub location: Option<Location>,
+    /// Media type of the cable plugged into the port (`MediaType` field
+    /// from `fac_MediaTypes` table of this port or the one on the other end
+    /// of the cable), `None` if not set
+    #[serde(default)]
+    pub media_type: Option<String>,
+    /// Colour of the cable plugged into the port (`Name` field from
+    /// `fac_ColorCoding` table of this port or the one on the other end of
+    /// the cable), `None` if not set
+    #[serde(default)]
+    pub color: Option<String>,
+    /// The chassis or stack the device is a member of, `None` for
+    /// standalone devices
+    #[serde(default)]
+    pub chassis: Option<Chassis>,
+}
+
+/// The chassis or stack a device is mounted in, as modelled by openDCIM's
+/// child devices (`ParentDevice` field from `fac_Device` table).
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Chassis {
+    /// Device ID of the chassis or stack (`DeviceID` field from
+    /// `fac_Device` table)
+    pub device_id: i32,
+    /// Name of the chassis or stack (`Label` field from `fac_Device` table)
+    pub label: String,
+    /// Management IP address of the chassis or stack master (`PrimaryIP`
+    /// field from `fac_Device` table), `None` if it isn't set or invalid
+    pub primary_ip: Option<IpAddr>,
+    /// Slot or stack member number of the device (`Position` field of the
+    /// child device)
+    pub slot: i32,
+}
+
+impl fmt::Display for Chassis {
+    /// Formats the chassis like `stack-h1-e2 slot 2`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} slot {}", self.label, self.slot)
+    }
+}
+
+impl Hop {
+    /// The device ID of the chassis or stack the device belongs to, or its
+    /// own if it is standalone.
+    pub fn unit_id(&self) -> i32 {
+        self.chassis
+            .as_ref()
+            .map_or(self.device_id, |chassis| chassis.device_id)
+    }
+
+    /// Describes the cable plugged into the port, like `blue Cat6A`, or
+    /// returns `None` if neither colour nor media type are set.
+    pub fn cable(&self) -> Option<String> {
+        let parts: Vec<&str> = [&self.color, &self.media_type]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// The identifier of the port stored in `column`.
+    pub fn identifier(&self, column: IdColumn) -> &str {
+        match column {
+            IdColumn::Notes => &self.port_notes,
+            IdColumn::Label => &self.port_label,
+        }
+    }
+
+    /// The first of `columns` holding an identifier for the port, or the
+    /// first of `columns` if the port has none.
+    pub(crate) fn id_column(&self, columns: &[IdColumn]) -> Option<IdColumn> {
+        columns
+            .iter()
+            .copied()
+            .find(|&column| !self.identifier(column).trim().is_empty())
+            .or_else(|| columns.first().copied())
+    }
+
+    /// Whether the identifier of the port in one of `columns` is `name`,
+    /// ignoring case like MySQL's default collation does.
+    pub(crate) fn is_named(&self, name: &str, columns: &[IdColumn]) -> bool {
+        columns
+            .iter()
+            .any(|&column| self.identifier(column).trim().eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the port has a combined identifier like `01.1.001-1,2` that
+    /// includes `socket`.
+    pub(crate) fn covers(&self, socket: &SocketId) -> bool {
+        [&self.port_notes, &self.port_label]
+            .into_iter()
+            .any(|name| match name.parse::<SocketId>() {
+                Ok(combined) => combined.is_combined() && combined.outlets().contains(socket),
+                Err(_) => false,
+            })
+    }
+}
+
+/// Completes the cable of two connected ports with what is only stored on
+/// the other end, openDCIM doesn't always keep both ends in sync.
+pub(crate) fn share_cable(a: &mut Hop, b: &mut Hop) {
+    for (a, b) in [
+        (&mut a.media_type, &mut b.media_type),
+        (&mut a.color, &mut b.color),
+    ] {
+        match (a.is_some(), b.is_some()) {
+            (true, false) => *b = a.clone(),
+            (false, true) => *a = b.clone(),
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Hop {
+    /// Formats the hop the way a technician looks for it, like
+    /// `blue Cat6A, PP-E2-03 port 17`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(cable) = self.cable() {
+            write!(f, "{cable}, ")?;
+        }
+        write!(f, "{} port {}", self.device_label, self.port_label)
+    }
+}
+
+/// Where a device is mounted.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Location {
+    /// Name of the data center or distributor room (`Name` field from
+    /// `fac_DataCenter` table)
+    pub data_center: String,
+    /// Location of the cabinet within the data center (`Location` field
+    /// from `fac_Cabinet` table)
+    pub cabinet: String,
+    /// Lowest rack unit the device occupies, 0 if unknown (`Position` field
+    /// from `fac_Device` table)
+    pub position: i32,
+    /// Number of rack units the device occupies (`Height` field from
+    /// `fac_Device` table)
+    pub height: i32,
+}
+
+impl fmt::Display for Location {
+    /// Formats the location like `DC Nord, cabinet R12, U40-41`, leaving out
+    /// what isn't known.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.data_center.is_empty() {
+            parts.push(self.data_center.clone());
+        }
+        if !self.cabinet.is_empty() {
+            parts.push(format!("cabinet {}", self.cabinet));
+        }
+        match (self.position, self.height) {
+            (0, _) => {}
+            (position, height) if height > 1 => {
+                parts.push(format!("U{position}-{}", position + height - 1))
+            }
+            (position, _) => parts.push(format!("U{position}")),
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hop(device_label: &str, port_number: i32) -> Hop {
+        Hop {
+            device_id: 1,
+            device_label: device_label.to_owned(),
+            port_number,
+            port_label: port_number.abs().to_string(),
+            port_notes: String::new(),
+            location: None,
+            media_type: None,
+            color: None,
+            chassis: None,
+        }
+    }
+
+    fn cable(hop: &mut Hop, color: Option<&str>, media_type: Option<&str>) {
+        hop.color = color.map(str::to_owned);
+        hop.media_type = media_type.map(str::to_owned);
+    }
+
+    #[test]
+    fn cable_leaves_out_what_is_unknown() {
+        let mut port = hop("PP-E2-03", 17);
+        assert_eq!(port.cable(), None);
+        cable(&mut port, None, Some("Cat6A"));
+        assert_eq!(port.cable().as_deref(), Some("Cat6A"));
+        cable(&mut port, Some("blue"), Some("Cat6A"));
+        assert_eq!(port.cable().as_deref(), Some("blue Cat6A"));
+        assert_eq!(port.to_string(), "blue Cat6A, PP-E2-03 port 17");
+    }
+
+    #[test]
+    fn cable_is_shared_between_ports_with_the_same_number() {
+        let (mut a, mut b) = (hop("PP-E2-03", -17), hop("PP-U1-01", -17));
+        cable(&mut a, None, Some("Cat6A"));
+        cable(&mut b, Some("blue"), None);
+        share_cable(&mut a, &mut b);
+
+        assert_eq!(a.cable().as_deref(), Some("blue Cat6A"));
+        assert_eq!(b.cable().as_deref(), Some("blue Cat6A"));
+    }
+
+    #[test]
+    fn cable_is_shared_between_ports_with_different_numbers() {
+        // Values set on both ends are kept, even if they disagree.
+        let (mut panel, mut switch) = (hop("PP-E2-03", -17), hop("sw-h1-e2", 5));
+        cable(&mut panel, Some("blue"), None);
+        cable(&mut switch, Some("red"), Some("Cat6A"));
+        share_cable(&mut panel, &mut switch);
+
+        assert_eq!(panel.cable().as_deref(), Some("blue Cat6A"));
+        assert_eq!(switch.cable().as_deref(), Some("red Cat6A"));
+    }
+
+    fn location(data_center: &str, cabinet: &str, position: i32, height: i32) -> String {
+        Location {
+            data_center: data_center.to_owned(),
+            cabinet: cabinet.to_owned(),
+            position,
+            height,
+        }
+        .to_string()
+    }
+
+    #[test]
+    fn location_leaves_out_what_is_unknown() {
+        assert_eq!(
+            location("DC Nord", "R12", 40, 2),
+            "DC Nord, cabinet R12, U40-41"
+        );
+        assert_eq!(
+            location("DC Nord", "R12", 40, 1),
+            "DC Nord, cabinet R12, U40"
+        );
+        assert_eq!(location("DC Nord", "", 40, 1), "DC Nord, U40");
+        assert_eq!(location("", "R12", 40, 2), "cabinet R12, U40-41");
+        assert_eq!(location("DC Nord", "R12", 0, 2), "DC Nord, cabinet R12");
+        assert_eq!(location("", "", 0, 0), "");
+    }
+}
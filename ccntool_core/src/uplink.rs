@@ -0,0 +1,39 @@
+//! The chain of uplinks connecting an access switch to the distribution and
+//! core layer.
+
+use crate::Hop;
+use serde::{Deserialize, Serialize};
+use std::{fmt, net::IpAddr};
+
+/// Maximum number of switches passed on the way to the core.
+pub const MAX_UPLINK_LEVELS: usize = 8;
+
+/// A connection from a switch port to a port of another switch, possibly
+/// through patch panels.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Uplink {
+    /// Port of the lower switch the uplink starts at
+    pub port: Hop,
+    /// Patch panel ports passed on the way
+    #[serde(default)]
+    pub path: Vec<Hop>,
+    /// Port of the upper switch the uplink ends at
+    pub switch_port: Hop,
+    /// IP address of the upper switch (`PrimaryIP` field from `fac_Device`
+    /// table), `None` if it isn't set or invalid
+    pub switch_ip: Option<IpAddr>,
+}
+
+impl fmt::Display for Uplink {
+    /// Formats the uplink like `sw-h1-e2 Te1/1/1 -> dsw-h1 Te1/0/5`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} -> {} {}",
+            self.port.device_label,
+            self.port.port_label,
+            self.switch_port.device_label,
+            self.switch_port.port_label
+        )
+    }
+}
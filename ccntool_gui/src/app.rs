@@ -1,21 +1,40 @@
 use crate::custom_frame::custom_window_frame;
-use ccntool_core::{connectdb, myquery, queryall};
+use ccntool_core::blocking::DcimClient;
+use ccntool_core::config::{Config, ConfigLayer};
+use ccntool_core::{Error, PortKind, PortLookup, RoomId, SearchIndex, SearchKind, Uplink};
 
 use eframe::egui;
 use egui::{FontId, FontTweak, RichText};
-use egui_dropdown::DropDownBox;
+
+/// Number of completions offered below a text field.
+const MAX_COMPLETIONS: usize = 10;
 
 #[derive(Default, serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct EguiSandbox {
     buf: String,
+    #[serde(skip)]
+    candidates: Vec<PortLookup>,
+    #[serde(skip)]
+    client: Option<DcimClient>,
     dcim_url: String,
     error: String,
+    kind_filter: Option<PortKind>,
+    #[serde(skip)]
     my_password: String,
+    my_profile: String,
     my_username: String,
-    ports: Vec<String>,
-    results: Vec<String>,
+    port_buf: String,
+    ports: SearchIndex,
+    result: Option<PortLookup>,
     settings_toggler: bool,
+    #[serde(skip)]
+    sockets: Vec<PortLookup>,
+    #[serde(skip)]
+    suggestions: Vec<String>,
+    switch_buf: String,
+    #[serde(skip)]
+    uplinks: Option<(i32, Vec<Uplink>)>,
 }
 
 impl EguiSandbox {
@@ -64,13 +83,90 @@ impl EguiSandbox {
 
         Self {
             buf: String::new(),
+            candidates: vec![],
+            client: None,
             dcim_url: String::new(),
             error: String::new(),
+            kind_filter: None,
             my_password: String::new(),
+            my_profile: String::new(),
             my_username: String::new(),
-            ports: vec![],
-            results: vec![],
+            port_buf: String::new(),
+            ports: SearchIndex::default(),
+            result: None,
             settings_toggler: false,
+            sockets: vec![],
+            suggestions: vec![],
+            switch_buf: String::new(),
+            uplinks: None,
+        }
+    }
+}
+
+impl EguiSandbox {
+    /// Returns the database client, connecting with the current settings if
+    /// there is none yet.
+    fn client(&mut self) -> Result<&DcimClient, Error> {
+        if self.client.is_none() {
+            // Settings left empty fall back to the config file and environment.
+            let nonempty = |value: &String| match value.len() {
+                0 => Option::None,
+                _ => Some(value.clone()),
+            };
+
+            let overrides = ConfigLayer {
+                profile: nonempty(&self.my_profile),
+                host: nonempty(&self.dcim_url),
+                username: nonempty(&self.my_username),
+                password: nonempty(&self.my_password),
+                ..Default::default()
+            };
+
+            self.client = Some(Config::load_with(overrides).and_then(DcimClient::connect)?);
+        }
+
+        Ok(self.client.as_ref().unwrap())
+    }
+
+    /// Stores the wallsockets of a switch or room, or the error for display.
+    fn set_sockets(&mut self, result: Result<Vec<PortLookup>, Error>) {
+        match result {
+            Ok(lookups) => {
+                self.error.clear();
+                self.sockets = lookups;
+            }
+            Err(error) => {
+                self.error = error.to_string();
+                eprintln!("Error: {error}");
+            }
+        }
+    }
+
+    /// Stores the result of a lookup, or its error for display.
+    fn set_result(&mut self, result: Result<PortLookup, Error>) {
+        self.suggestions.clear();
+        self.candidates.clear();
+        match result {
+            Ok(lookup) => {
+                self.error.clear();
+                self.result = Some(lookup);
+            }
+            Err(Error::NotFound { query, suggestions }) => {
+                self.error = format!("Nothing found for \"{query}\"");
+                self.suggestions = suggestions;
+            }
+            Err(Error::Ambiguous {
+                query,
+                count,
+                candidates,
+            }) => {
+                self.error = format!("\"{query}\" is used for {count} ports:");
+                self.candidates = candidates;
+            }
+            Err(error) => {
+                self.error = error.to_string();
+                eprintln!("Error: {error}");
+            }
         }
     }
 }
@@ -82,62 +178,53 @@ impl eframe::App for EguiSandbox {
 
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         custom_window_frame(ctx, frame, "TDQU", |ui| {
-            ui.add(DropDownBox::from_iter(
-                &self.ports,
+            egui::ComboBox::from_id_source("kindfilter")
+                .selected_text(match self.kind_filter {
+                    Some(kind) => kind.to_string(),
+                    None => "All kinds".to_owned(),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.kind_filter, None, "All kinds");
+                    for kind in PortKind::ALL {
+                        ui.selectable_value(&mut self.kind_filter, Some(kind), kind.to_string());
+                    }
+                });
+
+            let kind_filter = self.kind_filter;
+            autocomplete(
+                ui,
                 "portselector",
                 &mut self.buf,
-                |ui, text| ui.selectable_label(false, text),
-            ));
+                &self.ports,
+                |kind| match kind {
+                    SearchKind::Port(kind) => kind_filter.is_none() || kind_filter == Some(kind),
+                    _ => false,
+                },
+            );
 
             egui::Grid::new("Functionbuttons").show(ui, |ui| {
                 if ui.button("Query").clicked() {
-                    self.results.clear();
-
-                    let un = match &self.my_username.len() {
-                        0 => Option::None,
-                        _ => Some(self.my_username.clone()),
-                    };
-
-                    let pw = match &self.my_password.len() {
-                        0 => Option::None,
-                        _ => Some(self.my_password.clone()),
-                    };
-
-                    let burl = match &self.dcim_url.len() {
-                        0 => Option::None,
-                        _ => Some(self.dcim_url.clone()),
-                    };
-
-                    let conn = match connectdb(un, pw, burl) {
-                        Ok(pool) => pool,
-                        Err(error) => {
-                            let error_message = format!("Connection timeout: {error}");
-                            self.error = error_message;
-                            eprintln!("Error: {}", error);
-                            return;
-                        }
-                    };
-
-                    // TODO: catch if self.results[6] is = 1
-                    // Switchname: 1
-                    self.results = match myquery(conn, &self.buf) {
-                        Ok(rows) => {
-                            self.error.clear();
-                            rows
-                        }
-                        Err(error) => {
-                            let error_message = format!("Received garbage: {error}");
-                            self.error = error_message;
-                            eprintln!("Error: {error}");
-                            return;
-                        }
-                    };
+                    self.result = None;
+
+                    let notes = self.buf.clone();
+                    if notes.parse::<RoomId>().is_ok() {
+                        let result = self.client().and_then(|client| client.room_sockets(&notes));
+                        self.set_sockets(result);
+                    } else {
+                        let result = self.client().and_then(|client| client.lookup(&notes));
+                        self.set_result(result);
+                    }
                 }
 
                 if ui.button("Clear").clicked() {
-                    self.results.clear();
+                    self.result = None;
                     self.error.clear();
                     self.buf = String::new();
+                    self.switch_buf = String::new();
+                    self.port_buf = String::new();
+                    self.sockets.clear();
+                    self.suggestions.clear();
+                    self.candidates.clear();
                 }
 
                 if ui.add(egui::widgets::Button::new("⚙")).clicked() {
@@ -145,6 +232,67 @@ impl eframe::App for EguiSandbox {
                 }
             });
 
+            egui::CollapsingHeader::new("Switch port").show(ui, |ui| {
+                egui::Grid::new("Reversegrid").show(ui, |ui| {
+                    ui.label("Switch:");
+                    autocomplete(
+                        ui,
+                        "switchselector",
+                        &mut self.switch_buf,
+                        &self.ports,
+                        |kind| matches!(kind, SearchKind::Switch | SearchKind::SwitchIp),
+                    );
+                    ui.end_row();
+                    ui.label("Port:");
+                    ui.add(egui::TextEdit::singleline(&mut self.port_buf));
+                    ui.end_row();
+                    let has_switch = !self.switch_buf.trim().is_empty();
+                    let has_port = has_switch && !self.port_buf.trim().is_empty();
+                    let find = egui::widgets::Button::new("Find wallsocket");
+                    if ui.add_enabled(has_port, find).clicked() {
+                        self.result = None;
+
+                        let (switch, port) = (self.switch_buf.clone(), self.port_buf.clone());
+                        let result = self
+                            .client()
+                            .and_then(|client| client.reverse_lookup(&switch, &port));
+                        self.set_result(result);
+                    }
+                    let list = egui::widgets::Button::new("List wallsockets");
+                    if ui.add_enabled(has_switch, list).clicked() {
+                        let switch = self.switch_buf.clone();
+                        let result = self
+                            .client()
+                            .and_then(|client| client.switch_sockets(&switch));
+                        self.set_sockets(result);
+                    }
+                    ui.end_row();
+                });
+            });
+
+            if !self.sockets.is_empty() {
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("Socketgrid").striped(true).show(ui, |ui| {
+                            ui.strong("Wallsocket");
+                            ui.strong("Switch");
+                            ui.strong("Port");
+                            ui.strong("IP");
+                            ui.end_row();
+                            for lookup in &self.sockets {
+                                if ui.link(lookup.wallsocket()).clicked() {
+                                    self.result = Some(lookup.clone());
+                                }
+                                ui.label(&lookup.switch_label);
+                                ui.label(&lookup.switch_port);
+                                ui.label(lookup.switch_ip_text());
+                                ui.end_row();
+                            }
+                        });
+                    });
+            }
+
             if self.settings_toggler {
                 let settings_window = egui::Window::new("Settings")
                     .collapsible(false)
@@ -167,26 +315,22 @@ impl eframe::App for EguiSandbox {
                             ui.label("DCIM URL:");
                             ui.add(egui::TextEdit::singleline(&mut self.dcim_url));
                             ui.end_row();
+                            ui.label("Profile:");
+                            ui.add(egui::TextEdit::singleline(&mut self.my_profile));
+                            ui.end_row();
                             if ui.button("Close").clicked() {
-                                let un = match &self.my_username.len() {
-                                    0 => Option::None,
-                                    _ => Some(self.my_username.clone()),
-                                };
-
-                                let pw = match &self.my_password.len() {
-                                    0 => Option::None,
-                                    _ => Some(self.my_password.clone()),
-                                };
-
-                                let burl = match &self.dcim_url.len() {
-                                    0 => Option::None,
-                                    _ => Some(self.dcim_url.clone()),
-                                };
-
-                                self.ports = queryall(
-                                    connectdb(un, pw, burl).expect("Can't connect to database!"),
-                                )
-                                .unwrap();
+                                // Settings may have changed, so reconnect.
+                                self.client = None;
+                                match self.client().and_then(DcimClient::search_index) {
+                                    Ok(ports) => {
+                                        self.error.clear();
+                                        self.ports = ports;
+                                    }
+                                    Err(error) => {
+                                        self.error = error.to_string();
+                                        eprintln!("Error: {error}");
+                                    }
+                                }
                                 self.settings_toggler = false;
                             }
                         });
@@ -194,26 +338,90 @@ impl eframe::App for EguiSandbox {
                 });
             }
 
-            if self.results.is_empty() {
-                ui.label(
-                    RichText::new("Click butt0n, receive    🥓").font(FontId::proportional(14.0)),
-                );
-            } else {
+            let mut find_uplinks = None;
+            if let Some(lookup) = &self.result {
                 let mut text: String = format!(
-                    r#"Switchname: {}
+                    r#"Wallsocket: {}
+Switchname: {}
 IP: {}
 Switchport: {}
 Description: {}"#,
-                    self.results[0], self.results[3], self.results[2], self.results[1],
+                    lookup.wallsocket(),
+                    lookup.switch_label,
+                    lookup.switch_ip_text(),
+                    lookup.switch_port,
+                    lookup.port_notes,
                 );
+                if let Some(cable) = lookup.switch_cable() {
+                    text.push_str(&format!("\nSwitch cable: {cable}"));
+                }
+                if let Some(chassis) = lookup.switch_chassis() {
+                    text.push_str(&format!("\nChassis: {chassis}"));
+                }
+                if let Some(location) = lookup.switch_location() {
+                    text.push_str(&format!("\nSwitch location: {location}"));
+                }
+                for hop in lookup.patchpanels() {
+                    text.push_str(&format!("\nPatch panel: {hop}"));
+                    if let Some(location) = &hop.location {
+                        text.push_str(&format!(" ({location})"));
+                    }
+                }
 
                 ui.add(egui::TextEdit::multiline(&mut text).desired_width(f32::INFINITY));
-                // TODO: have this be evaluated via env as well
-                let url: String = format!(
-                    "https://{}/devices.php?DeviceID={}",
-                    self.dcim_url, self.results[4]
+                if let Some(hint) = lookup.kind.and_then(PortKind::hint) {
+                    ui.label(RichText::new(format!("ℹ {hint}")).font(FontId::proportional(14.0)));
+                }
+                if let Some(details) = &lookup.switch_details {
+                    egui::CollapsingHeader::new("Switch details").show(ui, |ui| {
+                        egui::Grid::new("Detailgrid").show(ui, |ui| {
+                            for (label, value) in details.fields() {
+                                ui.label(format!("{label}:"));
+                                ui.label(value);
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+                egui::CollapsingHeader::new("Uplinks").show(ui, |ui| match &self.uplinks {
+                    Some((switch, uplinks)) if *switch == lookup.switch_device_id => {
+                        if uplinks.is_empty() {
+                            ui.label("No uplinks found");
+                        }
+                        for uplink in uplinks {
+                            match uplink.switch_ip {
+                                Some(ip) => ui.label(format!("{uplink} ({ip})")),
+                                None => ui.label(uplink.to_string()),
+                            };
+                        }
+                    }
+                    _ => {
+                        if ui.button("Find uplinks").clicked() {
+                            find_uplinks = Some(lookup.switch_device_id);
+                        }
+                    }
+                });
+                if let Some(client) = &self.client {
+                    let url: String = client.config().device_url(lookup.switch_device_id);
+                    ui.hyperlink_to("View switch on openDCIM", url);
+                }
+            } else {
+                ui.label(
+                    RichText::new("Click butt0n, receive    🥓").font(FontId::proportional(14.0)),
                 );
-                ui.hyperlink_to("View switch on openDCIM", url);
+            }
+
+            if let Some(switch) = find_uplinks {
+                match self
+                    .client()
+                    .and_then(|client| client.uplinks(&switch.to_string()))
+                {
+                    Ok(uplinks) => self.uplinks = Some((switch, uplinks)),
+                    Err(error) => {
+                        self.error = error.to_string();
+                        eprintln!("Error: {error}");
+                    }
+                }
             }
 
             if !self.error.is_empty() {
@@ -223,6 +431,41 @@ Description: {}"#,
                         .color(egui::Color32::RED),
                 );
             }
+
+            let mut picked = None;
+            for candidate in &self.candidates {
+                let (device, port) = match candidate.path.first() {
+                    Some(hop) => (hop.device_label.as_str(), hop.port_label.as_str()),
+                    None => ("?", "?"),
+                };
+                let text = format!(
+                    "{device} port {port} → {} {}",
+                    candidate.switch_label, candidate.switch_port
+                );
+                if ui.link(text).clicked() {
+                    picked = Some(candidate.clone());
+                }
+            }
+            if picked.is_some() {
+                self.result = picked;
+            }
+
+            if !self.suggestions.is_empty() {
+                let mut picked = None;
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Did you mean:");
+                    for suggestion in &self.suggestions {
+                        if ui.link(suggestion).clicked() {
+                            picked = Some(suggestion.clone());
+                        }
+                    }
+                });
+                if let Some(notes) = picked {
+                    self.buf = notes.clone();
+                    let result = self.client().and_then(|client| client.lookup(&notes));
+                    self.set_result(result);
+                }
+            }
         });
     }
 
@@ -231,7 +474,7 @@ Description: {}"#,
     }
 
     fn on_close_event(&mut self) -> bool {
-        self.results.clear();
+        self.result = None;
         self.buf = String::new();
         self.error.clear();
         self.settings_toggler = false;
@@ -239,3 +482,43 @@ Description: {}"#,
         true
     }
 }
+
+/// Adds a single line text field offering the best matches of `index` for
+/// its content in a popup below it, restricted to the kinds `accept` allows.
+fn autocomplete(
+    ui: &mut egui::Ui,
+    id: &str,
+    buf: &mut String,
+    index: &SearchIndex,
+    accept: impl Fn(SearchKind) -> bool,
+) -> egui::Response {
+    let response = ui.add(egui::TextEdit::singleline(buf));
+    let popup_id = ui.make_persistent_id(id);
+    if response.changed() || response.gained_focus() {
+        ui.memory_mut(|memory| memory.open_popup(popup_id));
+    }
+
+    let completions: Vec<_> = index
+        .search(buf)
+        .into_iter()
+        .filter(|entry| accept(entry.kind) && entry.text != *buf)
+        .take(MAX_COMPLETIONS)
+        .collect();
+    if completions.is_empty() {
+        return response;
+    }
+
+    egui::popup_below_widget(ui, popup_id, &response, |ui| {
+        for entry in completions {
+            let text = match &entry.detail {
+                Some(detail) => format!("{}  ({detail})", entry.text),
+                None => entry.text.clone(),
+            };
+            if ui.selectable_label(false, text).clicked() {
+                *buf = entry.text.clone();
+            }
+        }
+    });
+
+    response
+}
@@ -1,10 +1,23 @@
-use ccntool_core::{connectdb, myquery};
+use ccntool_core::blocking::DcimClient;
+use ccntool_core::{
+    Config, Error, PortKind, PortLookup, RoomId, SearchEntry, SearchIndex, SearchKind,
+};
 
 use cursive::theme::{BorderStyle, Palette};
 use cursive::traits::*;
-use cursive::views::{Dialog, EditView, TextView};
+use cursive::views::{Dialog, EditView, LinearLayout, ListView, SelectView, TextView};
 use cursive::Cursive;
 
+/// Number of completions offered below the description.
+const MAX_COMPLETIONS: usize = 10;
+
+/// The database client and, once loaded, everything that can be searched
+/// for.
+struct Session {
+    client: DcimClient,
+    index: Option<SearchIndex>,
+}
+
 fn main() {
     let mut siv = cursive::default();
 
@@ -40,10 +53,21 @@ fn main() {
             .title("Enter a port description")
             .padding_lrtb(1, 1, 1, 0)
             .content(
-                EditView::new()
-                    .on_submit(show_popup)
-                    .with_name("description")
-                    .fixed_width(20),
+                LinearLayout::vertical()
+                    .child(
+                        EditView::new()
+                            .on_submit(show_popup)
+                            .on_edit(|s, text, _| update_completions(s, text))
+                            .with_name("description")
+                            .fixed_width(20),
+                    )
+                    .child(
+                        SelectView::<SearchEntry>::new()
+                            .on_submit(pick_completion)
+                            .with_name("completions")
+                            .scrollable()
+                            .max_height(MAX_COMPLETIONS),
+                    ),
             )
             .button("Ok", |s| {
                 let description = s
@@ -51,7 +75,17 @@ fn main() {
                     .unwrap();
 
                 show_popup(s, &description);
-            }),
+            })
+            .button("Search", |s| {
+                let description = s
+                    .call_on_name("description", |view: &mut EditView| view.get_content())
+                    .unwrap();
+
+                if load_index(s) {
+                    update_completions(s, &description);
+                }
+            })
+            .button("Switch port", show_reverse_dialog),
     );
 
     siv.run();
@@ -60,25 +94,276 @@ fn main() {
 fn show_popup(s: &mut Cursive, description: &str) {
     if description.is_empty() {
         s.add_layer(Dialog::info("Please enter a valid port description!"));
-    } else {
-        let conn =
-            connectdb(Option::None, Option::None, Option::None).expect("Can't connect to database");
-        let results = match myquery(conn, description) {
-            Ok(rows) => rows,
+    } else if description.parse::<RoomId>().is_ok() {
+        if let Some(client) = client(s) {
+            let result = client.room_sockets(description);
+            show_room(s, result);
+        }
+    } else if let Some(client) = client(s) {
+        let result = client.lookup(description);
+        show_result(s, result);
+    }
+}
+
+fn show_reverse_dialog(s: &mut Cursive) {
+    s.add_layer(
+        Dialog::new()
+            .title("Enter a switch port")
+            .padding_lrtb(1, 1, 1, 0)
+            .content(
+                ListView::new()
+                    .child(
+                        "Switch:",
+                        EditView::new().with_name("switch").fixed_width(20),
+                    )
+                    .child("Port:", EditView::new().with_name("port").fixed_width(20)),
+            )
+            .button("Ok", |s| {
+                let switch = s
+                    .call_on_name("switch", |view: &mut EditView| view.get_content())
+                    .unwrap();
+                let port = s
+                    .call_on_name("port", |view: &mut EditView| view.get_content())
+                    .unwrap();
+
+                if switch.is_empty() || port.is_empty() {
+                    s.add_layer(Dialog::info("Please enter a switch and a port!"));
+                } else if let Some(client) = client(s) {
+                    let result = client.reverse_lookup(&switch, &port);
+                    s.pop_layer();
+                    show_result(s, result);
+                }
+            })
+            .dismiss_button("Cancel"),
+    );
+}
+
+/// Returns the database client, connecting once and keeping it around for
+/// further queries. Shows the error and returns `None` if that fails.
+fn client(s: &mut Cursive) -> Option<&mut DcimClient> {
+    if s.user_data::<Session>().is_none() {
+        match Config::load().and_then(DcimClient::connect) {
+            Ok(client) => s.set_user_data(Session {
+                client,
+                index: None,
+            }),
+            Err(error) => {
+                s.add_layer(Dialog::info(error.to_string()));
+                return None;
+            }
+        }
+    }
+    s.user_data::<Session>().map(|session| &mut session.client)
+}
+
+/// Loads everything that can be searched for, once. Shows the error and
+/// returns `false` if that fails.
+fn load_index(s: &mut Cursive) -> bool {
+    if client(s).is_none() {
+        return false;
+    }
+
+    let session = s.user_data::<Session>().unwrap();
+    if session.index.is_none() {
+        match session.client.search_index() {
+            Ok(index) => session.index = Some(index),
             Err(error) => {
-                eprintln!("Error: {error}");
-                return;
+                s.add_layer(Dialog::info(error.to_string()));
+                return false;
             }
-        };
+        }
+    }
+    true
+}
+
+/// Lists the best matches for `query` below the description, as soon as
+/// the search index is loaded.
+fn update_completions(s: &mut Cursive, query: &str) {
+    let entries: Vec<SearchEntry> = match s.user_data::<Session>() {
+        Some(Session {
+            index: Some(index), ..
+        }) => index
+            .search(query)
+            .into_iter()
+            .take(MAX_COMPLETIONS)
+            .cloned()
+            .collect(),
+        _ => return,
+    };
+
+    s.call_on_name("completions", |view: &mut SelectView<SearchEntry>| {
+        view.clear();
+        for entry in entries {
+            let label = match &entry.detail {
+                Some(detail) => format!("{}  ({detail})", entry.text),
+                None => entry.text.clone(),
+            };
+            view.add_item(label, entry);
+        }
+    });
+}
 
-        let content = format!(
-            "Switchname: {}
+/// Looks up a picked wallsocket, or asks for the port of a picked switch.
+fn pick_completion(s: &mut Cursive, entry: &SearchEntry) {
+    let text = entry.text.clone();
+    match entry.kind {
+        SearchKind::Port(_) => {
+            s.call_on_name("description", |view: &mut EditView| {
+                view.set_content(text.clone())
+            });
+            show_popup(s, &text);
+        }
+        SearchKind::Switch | SearchKind::SwitchIp => {
+            show_reverse_dialog(s);
+            s.call_on_name("switch", |view: &mut EditView| view.set_content(text));
+        }
+    }
+}
+
+fn show_result(s: &mut Cursive, result: Result<PortLookup, Error>) {
+    let lookup = match result {
+        Ok(lookup) => lookup,
+        Err(Error::NotFound { query, suggestions }) if !suggestions.is_empty() => {
+            show_suggestions(s, &query, suggestions);
+            return;
+        }
+        Err(error) => {
+            s.add_layer(Dialog::info(error.to_string()));
+            return;
+        }
+    };
+
+    let mut content = format!(
+        "Wallsocket: {}
+Switchname: {}
 IP: {}
 Switchport: {}
 Beschreibung: {}",
-            results[0], results[3], results[2], results[1]
-        );
-        s.pop_layer();
-        s.add_layer(Dialog::around(TextView::new(content)).button("Quit", |s| s.quit()));
+        lookup.wallsocket(),
+        lookup.switch_label,
+        lookup.switch_ip_text(),
+        lookup.switch_port,
+        lookup.port_notes
+    );
+    if let Some(cable) = lookup.switch_cable() {
+        content.push_str(&format!("\nSwitch cable: {cable}"));
+    }
+    if let Some(chassis) = lookup.switch_chassis() {
+        content.push_str(&format!("\nChassis: {chassis}"));
+    }
+    if let Some(location) = lookup.switch_location() {
+        content.push_str(&format!("\nSwitch location: {location}"));
+    }
+    for hop in lookup.patchpanels() {
+        content.push_str(&format!("\nPatch panel: {hop}"));
+        if let Some(location) = &hop.location {
+            content.push_str(&format!(" ({location})"));
+        }
+    }
+    if let Some(hint) = lookup.kind.and_then(PortKind::hint) {
+        content.push_str(&format!("\nHinweis: {hint}"));
+    }
+    let mut dialog = Dialog::around(TextView::new(content));
+    if let Some(details) = &lookup.switch_details {
+        let details = details
+            .fields()
+            .into_iter()
+            .map(|(label, value)| format!("{label}: {value}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        dialog = dialog.button("Switch details", move |s| {
+            s.add_layer(
+                Dialog::around(TextView::new(details.clone()))
+                    .title("Switch details")
+                    .dismiss_button("Ok"),
+            );
+        });
+    }
+    let switch = lookup.switch_device_id.to_string();
+    dialog = dialog.button("Uplinks", move |s| show_uplinks(s, &switch));
+    s.pop_layer();
+    s.add_layer(dialog.button("Quit", |s| s.quit()));
+}
+
+/// Shows the uplinks of a switch to the distribution and core layer.
+fn show_uplinks(s: &mut Cursive, switch: &str) {
+    let Some(client) = client(s) else {
+        return;
+    };
+    let uplinks = match client.uplinks(switch) {
+        Ok(uplinks) => uplinks,
+        Err(error) => {
+            s.add_layer(Dialog::info(error.to_string()));
+            return;
+        }
+    };
+    if uplinks.is_empty() {
+        s.add_layer(Dialog::info("No uplinks found."));
+        return;
+    }
+
+    let content = uplinks
+        .iter()
+        .map(|uplink| match uplink.switch_ip {
+            Some(ip) => format!("{uplink} ({ip})"),
+            None => uplink.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    s.add_layer(
+        Dialog::around(TextView::new(content).scrollable())
+            .title("Uplinks")
+            .dismiss_button("Ok"),
+    );
+}
+
+/// Shows every wallsocket of a room with the switch port it is patched to.
+fn show_room(s: &mut Cursive, result: Result<Vec<PortLookup>, Error>) {
+    let lookups = match result {
+        Ok(lookups) => lookups,
+        Err(error) => {
+            s.add_layer(Dialog::info(error.to_string()));
+            return;
+        }
+    };
+
+    let content = lookups
+        .iter()
+        .map(|lookup| {
+            format!(
+                "{}  {} {} ({})",
+                lookup.wallsocket(),
+                lookup.switch_label,
+                lookup.switch_port,
+                lookup.switch_ip_text()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if content.is_empty() {
+        s.add_layer(Dialog::info(
+            "No wallsocket in this room is patched to a switch.",
+        ));
+        return;
+    }
+    s.add_layer(
+        Dialog::around(TextView::new(content).scrollable())
+            .title("Wallsockets")
+            .dismiss_button("Ok"),
+    );
+}
+
+/// Offers the wallsockets closest to a description that wasn't found.
+fn show_suggestions(s: &mut Cursive, query: &str, suggestions: Vec<String>) {
+    let mut dialog = Dialog::around(TextView::new(format!(
+        "Nothing found for \"{query}\", did you mean:"
+    )))
+    .title("Not found");
+    for suggestion in suggestions {
+        dialog = dialog.button(suggestion.clone(), move |s| {
+            s.pop_layer();
+            show_popup(s, &suggestion);
+        });
     }
+    s.add_layer(dialog.dismiss_button("Cancel"));
 }